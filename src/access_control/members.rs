@@ -208,6 +208,25 @@ impl MembersList {
         self.add_member(member, metadata);
     }
 
+    /// Whether the account belongs to the team directly or through one of its sub-teams.
+    pub fn is_in_team(&self, account: AccountId, team: &str) -> bool {
+        let team = Member::Team(team.to_string());
+        let mut visited = HashSet::new();
+        let mut stack = vec![Member::Account(account)];
+        while let Some(member) = stack.pop() {
+            if member == team {
+                return true;
+            }
+            if !visited.insert(member.clone()) {
+                continue;
+            }
+            if let Some(metadata) = self.members.get(&member) {
+                stack.extend(metadata.last_version().parents);
+            }
+        }
+        false
+    }
+
     pub fn get_moderators(&self) -> HashSet<Member> {
         self.members
             .get(&Member::Team("moderators".to_string()))
@@ -0,0 +1,369 @@
+use std::cmp::Ordering;
+
+use near_sdk::serde_json::json;
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use crate::common::charge_storage_deposit;
+use crate::notify::{get_text_mentions, notify_accounts};
+use crate::proposal::query::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::proposal::{ProposalId, ProposalRevision};
+use crate::rfp::RFPId;
+use crate::str_serializers::*;
+use crate::{Contract, Proposal};
+
+pub type CommentId = u64;
+
+/// Maximum number of characters of a comment.
+pub const MAX_COMMENT_LENGTH: usize = 5000;
+
+/// What a comment is attached to.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(tag = "type", content = "id")]
+pub enum CommentTarget {
+    Proposal(ProposalId),
+    RFP(RFPId),
+}
+
+/// Number of a comment snapshot. The first snapshot of a comment has revision 0 and every edit
+/// increments it.
+pub type CommentRevision = u32;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "comment_version")]
+pub enum VersionedComment {
+    V0(CommentV0),
+}
+
+pub type Comment = CommentV0;
+
+/// Comment whose previous snapshots are kept in `comment_revisions`.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct CommentV0 {
+    pub id: CommentId,
+    pub target: CommentTarget,
+    /// Comment this one replies to, `None` for the comments which start a thread.
+    pub parent_id: Option<CommentId>,
+    pub author_id: AccountId,
+    /// Revision of the proposal at the time the comment was added.
+    pub target_revision: Option<ProposalRevision>,
+    pub snapshot: CommentSnapshot,
+    /// Revision of `snapshot`, which is also the number of previous snapshots.
+    pub revision: CommentRevision,
+    /// The body and the previous snapshots of a deleted comment are cleared.
+    pub deleted: Option<CommentDeletion>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct CommentSnapshot {
+    pub editor_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub body: String,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct CommentDeletion {
+    pub deleted_by: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+}
+
+#[near(serializers=[json])]
+pub struct CommentsPage {
+    pub comments: Vec<VersionedComment>,
+    /// Offset to pass as `cursor` to fetch the next page, `None` when there are no more comments.
+    pub next_cursor: Option<u32>,
+}
+
+impl From<VersionedComment> for Comment {
+    fn from(vc: VersionedComment) -> Self {
+        match vc {
+            VersionedComment::V0(v0) => v0,
+        }
+    }
+}
+
+impl From<Comment> for VersionedComment {
+    fn from(c: Comment) -> Self {
+        VersionedComment::V0(c)
+    }
+}
+
+impl Contract {
+    pub(crate) fn get_comment_count(&self, target: CommentTarget) -> u32 {
+        self.comment_counts.get(&target).unwrap_or(0)
+    }
+
+    fn get_comment_internal(&self, id: CommentId) -> Comment {
+        self.comments.get(id).unwrap_or_else(|| panic!("Comment id {} not found", id)).into()
+    }
+
+    /// Returns the snapshot of the comment at the given revision.
+    pub(crate) fn get_comment_snapshot(
+        &self,
+        id: CommentId,
+        revision: CommentRevision,
+    ) -> Option<CommentSnapshot> {
+        let comment: Comment = self.comments.get(id)?.into();
+        match revision.cmp(&comment.revision) {
+            Ordering::Less => self.comment_revisions.get(&(id, revision)),
+            Ordering::Equal => Some(comment.snapshot),
+            Ordering::Greater => None,
+        }
+    }
+
+    /// Checks that the target exists and returns the current revision of a proposal target.
+    fn comment_target_revision(&self, target: CommentTarget) -> Option<ProposalRevision> {
+        match target {
+            CommentTarget::Proposal(proposal_id) => {
+                let proposal: Proposal = self.get_proposal(proposal_id).into();
+                Some(proposal.revision)
+            }
+            CommentTarget::RFP(rfp_id) => {
+                self.load_rfp(rfp_id);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn add_comment_internal(
+        &mut self,
+        target: CommentTarget,
+        parent_comment: Option<CommentId>,
+        body: String,
+    ) -> CommentId {
+        let target_revision = self.comment_target_revision(target);
+        require_comment_length(&body);
+        if let Some(parent_id) = parent_comment {
+            let parent = self.get_comment_internal(parent_id);
+            require!(parent.target == target, "The parent comment belongs to another thread");
+            require!(parent.deleted.is_none(), "Cannot reply to a deleted comment");
+        }
+
+        let initial_storage_usage = env::storage_usage();
+        let id = self.comments.len();
+        let author_id = env::predecessor_account_id();
+        let comment = Comment {
+            id,
+            target,
+            parent_id: parent_comment,
+            author_id: author_id.clone(),
+            target_revision,
+            snapshot: CommentSnapshot {
+                editor_id: author_id,
+                timestamp: env::block_timestamp(),
+                body: body.clone(),
+            },
+            revision: 0,
+            deleted: None,
+        };
+        self.comments.push(&comment.into());
+
+        match parent_comment {
+            Some(parent_id) => {
+                let index = self.comment_reply_counts.get(&parent_id).unwrap_or(0);
+                self.comment_replies.insert(&(parent_id, index), &id);
+                self.comment_reply_counts.insert(&parent_id, &(index + 1));
+            }
+            None => {
+                let index = self.comment_thread_counts.get(&target).unwrap_or(0);
+                self.comment_threads.insert(&(target, index), &id);
+                self.comment_thread_counts.insert(&target, &(index + 1));
+            }
+        }
+        self.comment_counts.insert(&target, &(self.get_comment_count(target) + 1));
+        charge_storage_deposit(initial_storage_usage);
+
+        notify_comment_mentions(id, target, get_text_mentions(&body));
+        id
+    }
+
+    pub(crate) fn edit_comment_internal(&mut self, id: CommentId, body: String) -> CommentId {
+        let mut comment = self.get_comment_internal(id);
+        let editor_id = env::predecessor_account_id();
+        require!(comment.author_id == editor_id, "Only the author can edit the comment");
+        require!(comment.deleted.is_none(), "Cannot edit a deleted comment");
+        require_comment_length(&body);
+
+        let old_mentions = get_text_mentions(&comment.snapshot.body);
+        let new_mentions = get_text_mentions(&body)
+            .into_iter()
+            .filter(|mention| !old_mentions.contains(mention))
+            .collect();
+        let initial_storage_usage = env::storage_usage();
+        let new_snapshot = CommentSnapshot { editor_id, timestamp: env::block_timestamp(), body };
+        let old_snapshot = std::mem::replace(&mut comment.snapshot, new_snapshot);
+        self.comment_revisions.insert(&(id, comment.revision), &old_snapshot);
+        comment.revision += 1;
+        let target = comment.target;
+        self.comments.replace(id, &comment.into());
+        charge_storage_deposit(initial_storage_usage);
+
+        notify_comment_mentions(id, target, new_mentions);
+        id
+    }
+
+    pub(crate) fn delete_comment_internal(&mut self, id: CommentId) -> CommentId {
+        let deleted_by = env::predecessor_account_id();
+        require!(
+            deleted_by == env::current_account_id() || self.has_moderator(deleted_by.clone()),
+            "Only moderators can delete comments"
+        );
+        let mut comment = self.get_comment_internal(id);
+        require!(comment.deleted.is_none(), "The comment is already deleted");
+
+        for revision in 0..comment.revision {
+            self.comment_revisions.remove(&(id, revision));
+        }
+        comment.snapshot.body = String::new();
+        comment.deleted = Some(CommentDeletion { deleted_by, timestamp: env::block_timestamp() });
+        let target = comment.target;
+        self.comments.replace(id, &comment.into());
+        self.comment_counts.insert(&target, &(self.get_comment_count(target) - 1));
+        id
+    }
+
+    /// Returns a page of the `total` comments of a list, `comment_at` giving the id of the comment
+    /// at an index of the list.
+    pub(crate) fn get_comments_page(
+        &self,
+        total: u32,
+        comment_at: impl Fn(u32) -> Option<CommentId>,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> CommentsPage {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        let start = cursor.unwrap_or(0).min(total);
+        let end = start.saturating_add(limit).min(total);
+        CommentsPage {
+            comments: (start..end)
+                .filter_map(comment_at)
+                .filter_map(|id| self.comments.get(id))
+                .collect(),
+            next_cursor: if end < total { Some(end) } else { None },
+        }
+    }
+}
+
+fn require_comment_length(body: &str) {
+    require!(!body.trim().is_empty(), "The comment cannot be empty");
+    require!(
+        body.chars().count() <= MAX_COMMENT_LENGTH,
+        format!("The comment cannot be longer than {} characters", MAX_COMMENT_LENGTH)
+    );
+}
+
+fn notify_comment_mentions(id: CommentId, target: CommentTarget, accounts: Vec<String>) {
+    let (target_type, target_id) = match target {
+        CommentTarget::Proposal(proposal_id) => ("proposal", proposal_id),
+        CommentTarget::RFP(rfp_id) => ("rfp", rfp_id),
+    };
+    notify_accounts(
+        env::current_account_id(),
+        accounts,
+        json!({
+            "type": "comment/mention",
+            "comment": id,
+            target_type: target_id,
+            "widgetAccountId": env::current_account_id(),
+            "notifier": env::predecessor_account_id(),
+        }),
+    );
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::json;
+    use near_sdk::NearToken;
+
+    use super::{Comment, CommentTarget};
+    use crate::test_fixtures::{proposal_body, set_deposit, set_predecessor, store_proposal};
+    use crate::Contract;
+
+    const DEPOSIT: NearToken = NearToken::from_millinear(100);
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        let mut proposal = store_proposal(&mut contract, "bob.near", proposal_body(json!({})));
+        proposal.revision = 3;
+        contract.proposals.replace(0, &proposal.into());
+        contract
+    }
+
+    #[test]
+    fn threads_edits_and_deletes() {
+        let mut contract = setup();
+        let target = CommentTarget::Proposal(0);
+
+        set_deposit("alice.near", DEPOSIT);
+        let first = contract.add_comment(target, None, "Hi @bob.near".to_string());
+        let second = contract.add_comment(target, None, "Another thread".to_string());
+        set_deposit("bob.near", DEPOSIT);
+        let reply = contract.add_comment(target, Some(first), "Thanks".to_string());
+        contract.edit_comment(reply, "Thanks a lot".to_string());
+
+        let page = contract.get_comments(target, None, Some(1));
+        assert_eq!(page.next_cursor, Some(1));
+        let comment: Comment = page.comments[0].clone().into();
+        assert_eq!(comment.id, first);
+        assert_eq!(comment.target_revision, Some(3));
+        let page = contract.get_comments(target, Some(1), None);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(Comment::from(page.comments[0].clone()).id, second);
+
+        let replies = contract.get_comment_replies(first, None, None);
+        let reply: Comment = replies.comments[0].clone().into();
+        assert_eq!(reply.parent_id, Some(first));
+        assert_eq!(reply.snapshot.body, "Thanks a lot");
+        assert_eq!(contract.get_comment_revision(reply.id, 0).body, "Thanks");
+
+        let summary = contract.get_proposal_summaries(vec![0]);
+        assert_eq!(summary[0].comment_count, 3);
+
+        set_predecessor("devhub.near");
+        contract.delete_comment(second);
+        let deleted: Comment = contract.get_comment(second).into();
+        assert_eq!(deleted.deleted.unwrap().deleted_by.as_str(), "devhub.near");
+        assert_eq!(deleted.snapshot.body, "");
+        assert_eq!(contract.get_proposal_summaries(vec![0])[0].comment_count, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the author can edit the comment")]
+    fn only_author_edits() {
+        let mut contract = setup();
+        set_deposit("alice.near", DEPOSIT);
+        let id = contract.add_comment(CommentTarget::Proposal(0), None, "Hi".to_string());
+        set_deposit("bob.near", DEPOSIT);
+        contract.edit_comment(id, "Edited".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only moderators can delete comments")]
+    fn only_moderators_delete() {
+        let mut contract = setup();
+        set_deposit("alice.near", DEPOSIT);
+        let id = contract.add_comment(CommentTarget::Proposal(0), None, "Hi".to_string());
+        contract.delete_comment(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "The comment cannot be longer than 5000 characters")]
+    fn caps_comment_length() {
+        let mut contract = setup();
+        set_deposit("alice.near", DEPOSIT);
+        contract.add_comment(CommentTarget::Proposal(0), None, "a".repeat(5001));
+    }
+}
@@ -1,13 +1,15 @@
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::{NearSchema, BorshStorageKey, CryptoHash};
+use near_sdk::{env, require, NearSchema, BorshStorageKey, CryptoHash, Promise, StorageUsage};
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, NearSchema)]
 #[serde(crate = "near_sdk::serde")]
 #[serde(tag = "post_version")]
 #[borsh(crate = "near_sdk::borsh")]
 pub enum VersionedPost {
+    /// Posts of the previous boards, their content is no longer stored.
     V0,
+    V1(crate::post::Post),
 }
 
 
@@ -36,4 +38,53 @@ pub enum StorageKey {
     LabelToRFPs,
     RFPLinkedProposals,
     LabelInfo,
+    ProposalRevisions,
+    /// `Comments` was used by the storage of the legacy posts.
+    CommentsV2,
+    CommentThreads,
+    CommentReplies,
+    CommentCounts,
+    RFPRevisions,
+    RFPQuestions,
+    RFPTemplates,
+    VotingPolicies,
+    ProposalVotes,
+    ReviewPolicies,
+    ProposalReviews,
+    ProposalConflicts,
+    AccountConflicts,
+    ProposalCoauthors,
+    CoauthorProposals,
+    ProposalTransfers,
+    ProposalFingerprints,
+    FingerprintProposals,
+    ShingleProposals,
+    ProposalSearchIndex,
+    RFPSearchIndex,
+    SearchTerms,
+    ProposalSpending,
+    BudgetOverrides,
+    PayoutTransactions,
+    CommentRevisions,
+    RFPQuestionCounts,
+    AccountVotes,
+    CommentThreadCounts,
+    CommentReplyCounts,
+    PostRevisions,
+}
+
+/// Requires the attached deposit to cover the storage added since `initial_storage_usage` and
+/// refunds the rest to the caller.
+pub fn charge_storage_deposit(initial_storage_usage: StorageUsage) {
+    let used = env::storage_usage().saturating_sub(initial_storage_usage);
+    let cost = env::storage_byte_cost().saturating_mul(used.into());
+    let deposit = env::attached_deposit();
+    require!(
+        deposit >= cost,
+        format!("Attach at least {} yoctoNEAR to cover the storage", cost.as_yoctonear())
+    );
+    let refund = deposit.saturating_sub(cost);
+    if !refund.is_zero() {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
 }
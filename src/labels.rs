@@ -0,0 +1,416 @@
+//! Hierarchy and lifecycle of the global labels: renaming, merging and deprecating a label.
+
+use std::collections::HashSet;
+
+use near_sdk::{env, near, require};
+
+use crate::proposal::{Proposal, ProposalId};
+use crate::rfp::RFPId;
+use crate::{Contract, ContractExt};
+
+/// Number of proposals and RFPs relabeled by a single call when no limit is given.
+const DEFAULT_MIGRATION_BATCH: u32 = 50;
+
+/// Pending replacement of the label `from` by the label `into` on every proposal and RFP.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LabelMigration {
+    pub from: String,
+    pub into: String,
+}
+
+/// The label `parent/child` is a child of the label `parent`.
+pub fn parent_label(label: &str) -> Option<&str> {
+    label.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+impl Contract {
+    fn assert_can_manage_labels(&self) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can manage labels"
+        );
+    }
+
+    fn assert_label_registered(&self, label: &str) {
+        require!(
+            self.global_labels_info.contains_key(label),
+            format!("Label {} is not registered", label)
+        );
+    }
+
+    fn assert_no_child_labels(&self, label: &str) {
+        require!(
+            !self.global_labels_info.values().any(|info| info.parent.as_deref() == Some(label)),
+            format!("Label {} has child labels", label)
+        );
+    }
+
+    /// Refuses the deprecated labels among `labels`, except the ones in `kept` which the record
+    /// already had.
+    pub(crate) fn assert_labels_not_deprecated(
+        &self,
+        labels: &HashSet<String>,
+        kept: &HashSet<String>,
+    ) {
+        for label in labels.difference(kept) {
+            require!(
+                !self.deprecated_labels.contains(label),
+                format!("Label {} is deprecated", label)
+            );
+        }
+    }
+
+    fn relabel_proposal(&mut self, id: ProposalId, migration: &LabelMigration) {
+        let mut proposal: Proposal = self.migrate_proposal_history(id);
+        proposal.snapshot.labels.remove(&migration.from);
+        proposal.snapshot.labels.insert(migration.into.clone());
+        self.proposals.replace(id.into(), &proposal.into());
+    }
+
+    fn relabel_rfp(&mut self, id: RFPId, migration: &LabelMigration) {
+        let mut rfp = self.load_rfp(id);
+        rfp.snapshot.labels.remove(&migration.from);
+        rfp.snapshot.labels.insert(migration.into.clone());
+        self.rfps.replace(id.into(), &rfp.into());
+    }
+
+    /// Relabels up to `limit` proposals, then RFPs, of the pending migrations in the order they
+    /// were started. The indexes keep the records which are not relabeled yet under the old
+    /// label, so that the next call picks up where this one stopped.
+    fn process_label_migrations(&mut self, limit: u32) {
+        let mut remaining = limit as usize;
+        while let Some(migration) = self.label_migrations.first().cloned() {
+            let mut old_proposals =
+                self.label_to_proposals.get(&migration.from).unwrap_or_default();
+            let mut batch: Vec<ProposalId> = old_proposals.iter().copied().collect();
+            batch.sort();
+            batch.truncate(remaining);
+            if !batch.is_empty() {
+                let mut new_proposals =
+                    self.label_to_proposals.get(&migration.into).unwrap_or_default();
+                for id in batch {
+                    self.relabel_proposal(id, &migration);
+                    old_proposals.remove(&id);
+                    new_proposals.insert(id);
+                    remaining -= 1;
+                }
+                self.label_to_proposals.insert(&migration.into, &new_proposals);
+                if old_proposals.is_empty() {
+                    self.label_to_proposals.remove(&migration.from);
+                } else {
+                    self.label_to_proposals.insert(&migration.from, &old_proposals);
+                }
+            }
+
+            let mut old_rfps = self.label_to_rfps.get(&migration.from).unwrap_or_default();
+            let mut batch: Vec<RFPId> = old_rfps.iter().copied().collect();
+            batch.sort();
+            batch.truncate(remaining);
+            if !batch.is_empty() {
+                let mut new_rfps = self.label_to_rfps.get(&migration.into).unwrap_or_default();
+                for id in batch {
+                    self.relabel_rfp(id, &migration);
+                    old_rfps.remove(&id);
+                    new_rfps.insert(id);
+                    remaining -= 1;
+                }
+                self.label_to_rfps.insert(&migration.into, &new_rfps);
+                if old_rfps.is_empty() {
+                    self.label_to_rfps.remove(&migration.from);
+                } else {
+                    self.label_to_rfps.insert(&migration.from, &old_rfps);
+                }
+            }
+
+            if !old_proposals.is_empty() || !old_rfps.is_empty() {
+                return;
+            }
+            self.label_migrations.remove(0);
+        }
+    }
+
+    fn start_label_migration(&mut self, from: String, into: String) -> Vec<LabelMigration> {
+        self.label_migrations.push(LabelMigration { from, into });
+        self.process_label_migrations(DEFAULT_MIGRATION_BATCH);
+        self.label_migrations.clone()
+    }
+}
+
+#[near]
+impl Contract {
+    /// Renames the registered label and relabels its proposals and RFPs. Returns the migrations
+    /// which are not finished yet, see `continue_label_migrations`.
+    #[payable]
+    pub fn rename_label(&mut self, label: String, new_label: String) -> Vec<LabelMigration> {
+        self.assert_can_manage_labels();
+        self.assert_label_registered(&label);
+        require!(
+            !self.global_labels_info.contains_key(&new_label),
+            format!("Label {} is already registered", new_label)
+        );
+
+        self.assert_no_child_labels(&label);
+        let parent = parent_label(&new_label).map(str::to_string);
+        if let Some(parent) = &parent {
+            self.assert_label_registered(parent);
+        }
+
+        let mut label_info = (*self.global_labels_info).remove(&label).unwrap();
+        label_info.parent = parent;
+        (*self.global_labels_info).insert(new_label.clone(), label_info);
+        if self.deprecated_labels.remove(&label) {
+            self.deprecated_labels.insert(new_label.clone());
+        }
+        self.start_label_migration(label, new_label)
+    }
+
+    /// Replaces the label `from` by the label `into` on every proposal and RFP and unregisters
+    /// `from`. Returns the migrations which are not finished yet.
+    #[payable]
+    pub fn merge_labels(&mut self, from: String, into: String) -> Vec<LabelMigration> {
+        self.assert_can_manage_labels();
+        self.assert_label_registered(&from);
+        self.assert_label_registered(&into);
+        require!(from != into, "Cannot merge a label into itself");
+        self.assert_no_child_labels(&from);
+
+        (*self.global_labels_info).remove(&from);
+        self.deprecated_labels.remove(&from);
+        self.start_label_migration(from, into)
+    }
+
+    /// Refuses the label on new proposals and RFPs. The records which already have it keep it.
+    #[payable]
+    pub fn deprecate_label(&mut self, label: String) {
+        self.assert_can_manage_labels();
+        self.assert_label_registered(&label);
+        require!(
+            self.deprecated_labels.insert(label.clone()),
+            format!("Label {} is already deprecated", label)
+        );
+    }
+
+    /// Relabels up to `limit` more records of the pending migrations.
+    #[payable]
+    pub fn continue_label_migrations(&mut self, limit: Option<u32>) -> Vec<LabelMigration> {
+        self.assert_can_manage_labels();
+        self.process_label_migrations(limit.unwrap_or(DEFAULT_MIGRATION_BATCH));
+        self.label_migrations.clone()
+    }
+
+    pub fn get_label_migrations(&self) -> Vec<LabelMigration> {
+        self.label_migrations.clone()
+    }
+
+    /// Registered labels below the label in the hierarchy.
+    pub fn get_label_descendants(&self, label: String) -> Vec<String> {
+        let mut descendants: Vec<String> = self
+            .global_labels_info
+            .iter()
+            .filter(|(_, info)| {
+                let mut parent = info.parent.clone();
+                while let Some(ancestor) = parent {
+                    if ancestor == label {
+                        return true;
+                    }
+                    parent = self
+                        .global_labels_info
+                        .get()
+                        .get(&ancestor)
+                        .and_then(|info| info.parent.clone());
+                }
+                false
+            })
+            .map(|(value, _)| value.clone())
+            .collect();
+        descendants.sort();
+        descendants
+    }
+
+    pub fn get_deprecated_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.deprecated_labels.iter().cloned().collect();
+        labels.sort();
+        labels
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::{self, json};
+
+    use super::LabelMigration;
+    use crate::rfp::{RFPSnapshot, RFP};
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::{Contract, LabelInfoExtended, Proposal};
+
+    fn push_proposal(contract: &mut Contract, labels: &[&str]) {
+        let mut proposal = store_proposal(contract, "bob.near", proposal_body(json!({})));
+        proposal.snapshot.labels = labels.iter().map(|label| label.to_string()).collect();
+        for label in &proposal.snapshot.labels {
+            let mut proposals = contract.label_to_proposals.get(label).unwrap_or_default();
+            proposals.insert(proposal.id);
+            contract.label_to_proposals.insert(label, &proposals);
+        }
+        contract.proposals.replace(proposal.id.into(), &proposal.into());
+    }
+
+    fn push_rfp(contract: &mut Contract, label: &str) {
+        let snapshot: RFPSnapshot = serde_json::from_value(json!({
+            "editor_id": "devhub.near",
+            "timestamp": "0",
+            "block_height": "0",
+            "labels": [label],
+            "rfp_body_version": "V0",
+            "name": "rfp",
+            "summary": "sum",
+            "description": "description",
+            "submission_deadline": "2000",
+            "timeline": {"status": "ACCEPTING_SUBMISSIONS"},
+            "linked_proposals": [],
+        }))
+        .unwrap();
+        let rfp = RFP {
+            id: contract.rfps.len().try_into().unwrap(),
+            author_id: "devhub.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot,
+            revision: 0,
+            legacy_block_heights: vec![],
+        };
+        contract.label_to_rfps.insert(&label.to_string(), &HashSet::from([rfp.id]));
+        contract.rfps.push(&rfp.into());
+    }
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        let labels: Vec<LabelInfoExtended> = serde_json::from_value(json!([
+            {"value": "infra", "title": null, "color": null},
+            {"value": "tooling", "title": null, "color": null},
+            {"value": "events", "title": null, "color": null},
+        ]))
+        .unwrap();
+        contract.set_global_labels(labels);
+        contract
+    }
+
+    fn proposal_labels(contract: &Contract, id: u32) -> HashSet<String> {
+        Proposal::from(contract.get_proposal(id)).snapshot.labels
+    }
+
+    #[test]
+    fn renames_labels_in_batches() {
+        let mut contract = setup();
+        for _ in 0..3 {
+            push_proposal(&mut contract, &["infra", "events"]);
+        }
+        push_rfp(&mut contract, "infra");
+
+        let migration = LabelMigration { from: "infra".into(), into: "infrastructure".into() };
+        contract.label_migrations.push(migration.clone());
+        (*contract.global_labels_info).remove("infra");
+        assert_eq!(contract.continue_label_migrations(Some(2)), vec![migration]);
+        assert!(proposal_labels(&contract, 1).contains("infrastructure"));
+        assert!(proposal_labels(&contract, 2).contains("infra"));
+        assert!(contract.continue_label_migrations(None).is_empty());
+
+        assert_eq!(contract.get_proposals_by_label("infrastructure".into(), None), vec![0, 1, 2]);
+        assert!(contract.get_proposals_by_label("infra".into(), None).is_empty());
+        assert_eq!(contract.get_rfps_by_label("infrastructure".into(), None), vec![0]);
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert!(rfp.snapshot.labels.contains("infrastructure"));
+        assert_eq!(
+            proposal_labels(&contract, 2),
+            HashSet::from(["infrastructure".into(), "events".into()])
+        );
+    }
+
+    #[test]
+    fn merges_labels() {
+        let mut contract = setup();
+        push_proposal(&mut contract, &["infra", "tooling"]);
+        push_proposal(&mut contract, &["tooling"]);
+        assert!(contract.merge_labels("tooling".into(), "infra".into()).is_empty());
+
+        assert_eq!(contract.get_proposals_by_label("infra".into(), None), vec![0, 1]);
+        assert_eq!(proposal_labels(&contract, 0), HashSet::from(["infra".into()]));
+        let labels: Vec<_> = contract.get_global_labels().into_iter().map(|l| l.value).collect();
+        assert_eq!(labels, vec!["events", "infra"]);
+
+        contract.rename_label("events".into(), "meetups".into());
+        assert!(contract.global_labels_info.contains_key("meetups"));
+    }
+
+    #[test]
+    fn queries_label_descendants() {
+        let mut contract = setup();
+        let labels: Vec<LabelInfoExtended> = serde_json::from_value(json!([
+            {"value": "infra", "title": null, "color": null},
+            {"value": "infra/indexers", "title": null, "color": null, "description": "Indexers"},
+            {"value": "infra/indexers/graphql", "title": null, "color": null},
+            {"value": "events", "title": null, "color": null},
+        ]))
+        .unwrap();
+        contract.set_global_labels(labels);
+        push_proposal(&mut contract, &["infra/indexers"]);
+        push_proposal(&mut contract, &["infra/indexers/graphql"]);
+        push_proposal(&mut contract, &["events"]);
+
+        assert_eq!(
+            contract.get_label_descendants("infra".into()),
+            vec!["infra/indexers", "infra/indexers/graphql"]
+        );
+        assert!(contract.get_proposals_by_label("infra".into(), None).is_empty());
+        assert_eq!(contract.get_proposals_by_label("infra".into(), Some(true)), vec![0, 1]);
+        let global_labels = near_sdk::serde_json::to_value(contract.get_global_labels()).unwrap();
+        assert_eq!(global_labels[2]["parent"], "infra");
+        assert_eq!(global_labels[2]["description"], "Indexers");
+
+        contract.rename_label("infra/indexers/graphql".into(), "infra/graphql".into());
+        assert_eq!(contract.get_proposals_by_label("infra/graphql".into(), None), vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent label infra of infra/indexers is not registered")]
+    fn requires_registered_parents() {
+        let mut contract = setup();
+        let labels: Vec<LabelInfoExtended> = serde_json::from_value(json!([
+            {"value": "infra/indexers", "title": null, "color": null},
+        ]))
+        .unwrap();
+        contract.set_global_labels(labels);
+    }
+
+    #[test]
+    #[should_panic(expected = "Label infra has child labels")]
+    fn keeps_parents_of_child_labels() {
+        let mut contract = setup();
+        let labels: Vec<LabelInfoExtended> = serde_json::from_value(json!([
+            {"value": "infra", "title": null, "color": null},
+            {"value": "infra/indexers", "title": null, "color": null},
+        ]))
+        .unwrap();
+        contract.set_global_labels(labels);
+        contract.rename_label("infra".into(), "infrastructure".into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Label events is deprecated")]
+    fn refuses_deprecated_labels_on_new_proposals() {
+        let mut contract = setup();
+        push_proposal(&mut contract, &["events"]);
+        contract.deprecate_label("events".into());
+        assert_eq!(contract.get_deprecated_labels(), vec!["events"]);
+
+        // Proposals which already have the label keep it.
+        set_predecessor("bob.near");
+        contract.edit_proposal(0, proposal_body(json!({})), HashSet::from(["events".into()]));
+
+        set_predecessor("bob.near");
+        contract.add_proposal(proposal_body(json!({})), HashSet::from(["events".into()]), Some(0));
+    }
+}
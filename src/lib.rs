@@ -1,25 +1,51 @@
 pub mod access_control;
+pub mod comment;
 pub mod community;
 pub mod debug;
+pub mod labels;
 pub mod migrations;
 mod notify;
 pub mod common;
+pub mod post;
 pub mod proposal;
 pub mod rfp;
+pub mod search;
 pub mod stats;
 pub mod str_serializers;
 pub mod web4;
 
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test_fixtures;
+
 use crate::access_control::members::ActionType;
 use crate::access_control::members::Member;
 use crate::access_control::AccessControl;
+use comment::{
+    CommentId, CommentRevision, CommentSnapshot, CommentTarget, CommentsPage, VersionedComment,
+};
 use community::*;
 
 use common::*;
-use proposal::timeline::{TimelineStatusV1, TimelineStatus, VersionedTimelineStatus};
+use post::{PostRevision, PostSnapshot};
+use proposal::diff::ProposalDiff;
+use proposal::query::{ProposalFilters, ProposalSummariesPage, ProposalsPage};
+use proposal::timeline::transitions::allowed_transitions;
+use proposal::timeline::{TimelineStatusKind, TimelineStatusV1, TimelineStatus, VersionedTimelineStatus};
 use proposal::*;
+use proposal::budgets::ProposalSpending;
+use proposal::categories::ProposalCategory;
+use proposal::conflicts::ConflictDeclaration;
+use proposal::review::{ProposalReviews, ReviewPolicy};
+use proposal::similarity::{FingerprintBackfill, ProposalFingerprint};
+use proposal::transfer::ProposalOwnership;
+use proposal::voting::{ProposalVotes, Vote, VotingPolicy};
+use labels::LabelMigration;
+use rfp::questions::{RFPQuestion, RFPQuestionId};
+use rfp::templates::RFPTemplate;
+use search::{SearchBackfill, SearchDocument};
 use rfp::{
-    RFPId, RFPSnapshot, TimelineStatus as RFPTimelineStatus, VersionedRFP, VersionedRFPBody, RFP,
+    ClosedRFPsPage, RFPId, RFPRemainingBudget, RFPRevision, RFPSnapshot, RFPSummariesPage, RFPSummary, TimelineStatus as RFPTimelineStatus,
+    VersionedRFP, VersionedRFPBody, RFP,
 };
 
 use devhub_common::{social_db_contract, SetReturnType};
@@ -29,7 +55,7 @@ use near_sdk::collections::{LookupMap, UnorderedMap, Vector};
 use near_sdk::store::Lazy;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::serde_json::{json, Number, Value};
-use near_sdk::{env, near, require, AccountId, NearSchema, PanicOnDefault, Promise};
+use near_sdk::{env, near, require, AccountId, CryptoHash, NearSchema, PanicOnDefault, Promise};
 use web4::types::{Web4Request, Web4Response};
 
 use std::collections::{HashSet, HashMap};
@@ -44,18 +70,55 @@ const ROOT_POST_ID: u64 = u64::MAX;
 #[derive(PanicOnDefault)]
 pub struct Contract {
     pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
     pub post_to_parent: LookupMap<PostId, PostId>,
     pub post_to_children: LookupMap<PostId, Vec<PostId>>,
     pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
     pub access_control: AccessControl,
     pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
     pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
     pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
     pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
-    pub proposal_categories: Vec<String>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<ProposalCategory>,
+    pub proposal_spending: LookupMap<ProposalId, ProposalSpending>,
+    pub budget_overrides: LookupMap<ProposalId, AccountId>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
     pub rfps: Vector<VersionedRFP>,
     pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub proposal_search_index: LookupMap<String, HashMap<ProposalId, u32>>,
+    pub rfp_search_index: LookupMap<String, HashMap<RFPId, u32>>,
+    pub search_terms: LookupMap<SearchDocument, Vec<String>>,
+    pub search_backfill: SearchBackfill,
     pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub deprecated_labels: HashSet<String>,
+    pub label_migrations: Vec<LabelMigration>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
     pub communities: UnorderedMap<CommunityHandle, Community>,
     pub featured_communities: Vec<FeaturedCommunity>,
     pub available_addons: UnorderedMap<AddOnId, AddOn>,
@@ -65,22 +128,59 @@ pub struct Contract {
 impl Contract {
     #[init]
     pub fn new() -> Self {
-        migrations::state_version_write(&migrations::StateVersion::V11);
+        migrations::state_version_write(&migrations::StateVersion::V28);
 
         let mut contract = Self {
             posts: Vector::new(StorageKey::Posts),
+            post_revisions: LookupMap::new(StorageKey::PostRevisions),
             post_to_parent: LookupMap::new(StorageKey::PostToParent),
             post_to_children: LookupMap::new(StorageKey::PostToChildren),
             label_to_posts: UnorderedMap::new(StorageKey::LabelToPostsV2),
             access_control: AccessControl::default(),
             authors: UnorderedMap::new(StorageKey::AuthorToAuthorPosts),
             proposals: Vector::new(StorageKey::Proposals),
+            proposal_revisions: LookupMap::new(StorageKey::ProposalRevisions),
             label_to_proposals: UnorderedMap::new(StorageKey::LabelToProposals),
             author_proposals: UnorderedMap::new(StorageKey::AuthorProposals),
+            payout_transactions: LookupMap::new(StorageKey::PayoutTransactions),
+            proposal_coauthors: LookupMap::new(StorageKey::ProposalCoauthors),
+            coauthor_proposals: LookupMap::new(StorageKey::CoauthorProposals),
+            proposal_transfers: LookupMap::new(StorageKey::ProposalTransfers),
+            proposal_fingerprints: LookupMap::new(StorageKey::ProposalFingerprints),
+            fingerprint_proposals: LookupMap::new(StorageKey::FingerprintProposals),
+            shingle_proposals: LookupMap::new(StorageKey::ShingleProposals),
+            duplicate_window: None,
+            fingerprint_backfill: FingerprintBackfill::default(),
             proposal_categories: default_categories(),
+            proposal_spending: LookupMap::new(StorageKey::ProposalSpending),
+            budget_overrides: LookupMap::new(StorageKey::BudgetOverrides),
+            voting_policies: UnorderedMap::new(StorageKey::VotingPolicies),
+            proposal_votes: LookupMap::new(StorageKey::ProposalVotes),
+            account_votes: LookupMap::new(StorageKey::AccountVotes),
+            review_policies: UnorderedMap::new(StorageKey::ReviewPolicies),
+            proposal_reviews: LookupMap::new(StorageKey::ProposalReviews),
+            proposal_conflicts: LookupMap::new(StorageKey::ProposalConflicts),
+            account_conflicts: LookupMap::new(StorageKey::AccountConflicts),
             rfps: Vector::new(StorageKey::RFPs),
             label_to_rfps: UnorderedMap::new(StorageKey::LabelToRFPs),
+            rfp_revisions: LookupMap::new(StorageKey::RFPRevisions),
+            rfp_questions: LookupMap::new(StorageKey::RFPQuestions),
+            rfp_question_counts: LookupMap::new(StorageKey::RFPQuestionCounts),
+            rfp_templates: Vector::new(StorageKey::RFPTemplates),
+            proposal_search_index: LookupMap::new(StorageKey::ProposalSearchIndex),
+            rfp_search_index: LookupMap::new(StorageKey::RFPSearchIndex),
+            search_terms: LookupMap::new(StorageKey::SearchTerms),
+            search_backfill: SearchBackfill::default(),
             global_labels_info: Lazy::new(StorageKey::LabelInfo, HashMap::new()),
+            deprecated_labels: HashSet::new(),
+            label_migrations: Vec::new(),
+            comments: Vector::new(StorageKey::CommentsV2),
+            comment_revisions: LookupMap::new(StorageKey::CommentRevisions),
+            comment_threads: LookupMap::new(StorageKey::CommentThreads),
+            comment_thread_counts: LookupMap::new(StorageKey::CommentThreadCounts),
+            comment_replies: LookupMap::new(StorageKey::CommentReplies),
+            comment_reply_counts: LookupMap::new(StorageKey::CommentReplyCounts),
+            comment_counts: LookupMap::new(StorageKey::CommentCounts),
             communities: UnorderedMap::new(StorageKey::Communities),
             featured_communities: Vec::new(),
             available_addons: UnorderedMap::new(StorageKey::AddOns),
@@ -95,34 +195,176 @@ impl Contract {
             ids
                 .into_iter()
                 .filter_map(|id| self.proposals.get(id.into()))
+                .map(|proposal| Proposal::from(proposal).into())
                 .collect()
         } else {
-            self.proposals.to_vec()
+            self.proposals.iter().map(|proposal| Proposal::from(proposal).into()).collect()
+        }
+    }
+
+    pub fn get_proposals_page(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+        filters: Option<ProposalFilters>,
+    ) -> ProposalsPage {
+        let (proposals, next_cursor) =
+            self.query_proposals(cursor, limit, filters.unwrap_or_default());
+        ProposalsPage {
+            proposals: proposals.into_iter().map(Into::into).collect(),
+            next_cursor,
+        }
+    }
+
+    pub fn get_proposal_summaries(&self, ids: Vec<ProposalId>) -> Vec<ProposalSummary> {
+        ids.into_iter()
+            .filter_map(|id| self.proposals.get(id.into()))
+            .map(|proposal| self.get_proposal_summary(proposal.into()))
+            .collect()
+    }
+
+    pub fn get_proposal_summaries_page(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+        filters: Option<ProposalFilters>,
+    ) -> ProposalSummariesPage {
+        let (proposals, next_cursor) =
+            self.query_proposals(cursor, limit, filters.unwrap_or_default());
+        ProposalSummariesPage {
+            proposals: proposals
+                .into_iter()
+                .map(|proposal| self.get_proposal_summary(proposal))
+                .collect(),
+            next_cursor,
         }
     }
 
     pub fn get_proposal(&self, proposal_id: ProposalId) -> VersionedProposal {
-        self.proposals
+        let proposal: Proposal = self
+            .proposals
             .get(proposal_id.into())
             .unwrap_or_else(|| panic!("Proposal id {} not found", proposal_id))
+            .into();
+        proposal.into()
+    }
+
+    pub fn get_proposal_revision(
+        &self,
+        proposal_id: ProposalId,
+        revision: ProposalRevision,
+    ) -> ProposalSnapshot {
+        self.get_proposal_snapshot(proposal_id, revision).unwrap_or_else(|| {
+            panic!("Revision {} of proposal id {} not found", revision, proposal_id)
+        })
+    }
+
+    /// Returns the previous snapshots of the proposal starting from revision `from`. The current
+    /// snapshot is not included.
+    pub fn get_proposal_history(
+        &self,
+        proposal_id: ProposalId,
+        from: Option<ProposalRevision>,
+        limit: Option<u32>,
+    ) -> Vec<ProposalSnapshot> {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        let limit =
+            limit.unwrap_or(proposal::query::DEFAULT_PAGE_LIMIT).min(proposal::query::MAX_PAGE_LIMIT);
+        let from = from.unwrap_or(0).min(proposal.revision);
+        let to = from.saturating_add(limit).min(proposal.revision);
+        (from..to).map(|revision| self.get_proposal_revision(proposal_id, revision)).collect()
+    }
+
+    pub fn diff_proposal_revisions(
+        &self,
+        id: ProposalId,
+        from_rev: ProposalRevision,
+        to_rev: ProposalRevision,
+    ) -> ProposalDiff {
+        proposal::diff::diff_proposal_snapshots(
+            id,
+            from_rev,
+            self.get_proposal_revision(id, from_rev),
+            to_rev,
+            self.get_proposal_revision(id, to_rev),
+        )
+    }
+
+    /// Returns the statuses `account` may move the proposal to, including its current status when
+    /// the account may update the details of that status.
+    pub fn get_allowed_transitions(
+        &self,
+        proposal_id: ProposalId,
+        account: AccountId,
+    ) -> Vec<TimelineStatusKind> {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        let roles = self.get_timeline_roles(&proposal, &account);
+        let current = proposal.snapshot.body.latest_version().timeline.latest_version().kind();
+        allowed_transitions(current, &roles)
     }
 
     pub fn get_all_proposal_ids(&self) -> Vec<ProposalId> {
         (0..self.proposals.len().try_into().unwrap()).collect()
     }
 
+    /// RFPs past their submission deadline are returned in Evaluation, see `close_expired_rfps`.
     pub fn get_rfps(&self) -> Vec<VersionedRFP> {
-        self.rfps.to_vec()
+        let now = env::block_timestamp();
+        self.rfps.iter().map(|rfp| RFP::from(rfp).at(now).into()).collect()
     }
 
     pub fn get_rfp(&self, rfp_id: RFPId) -> VersionedRFP {
-        self.rfps.get(rfp_id.into()).unwrap_or_else(|| panic!("RFP id {} not found", rfp_id))
+        self.load_rfp(rfp_id).at(env::block_timestamp()).into()
+    }
+
+    pub fn get_rfp_revision(&self, rfp_id: RFPId, revision: RFPRevision) -> RFPSnapshot {
+        self.get_rfp_snapshot(rfp_id, revision)
+            .unwrap_or_else(|| panic!("Revision {} of RFP id {} not found", revision, rfp_id))
+    }
+
+    /// Returns the previous snapshots of the RFP starting from revision `from`. The current
+    /// snapshot is not included.
+    pub fn get_rfp_history(
+        &self,
+        rfp_id: RFPId,
+        from: Option<RFPRevision>,
+        limit: Option<u32>,
+    ) -> Vec<RFPSnapshot> {
+        let rfp = self.load_rfp(rfp_id);
+        let limit =
+            limit.unwrap_or(proposal::query::DEFAULT_PAGE_LIMIT).min(proposal::query::MAX_PAGE_LIMIT);
+        let from = from.unwrap_or(0).min(rfp.revision);
+        let to = from.saturating_add(limit).min(rfp.revision);
+        (from..to).map(|revision| self.get_rfp_revision(rfp_id, revision)).collect()
     }
 
     pub fn get_all_rfp_ids(&self) -> Vec<RFPId> {
         (0..self.rfps.len().try_into().unwrap()).collect()
     }
 
+    pub fn get_rfp_summaries(&self, ids: Vec<RFPId>) -> Vec<RFPSummary> {
+        ids.into_iter()
+            .filter_map(|id| self.rfps.get(id.into()))
+            .map(|rfp| RFP::from(rfp).into())
+            .collect()
+    }
+
+    pub fn get_rfp_summaries_page(
+        &self,
+        cursor: Option<RFPId>,
+        limit: Option<u32>,
+    ) -> RFPSummariesPage {
+        let limit =
+            limit.unwrap_or(proposal::query::DEFAULT_PAGE_LIMIT).min(proposal::query::MAX_PAGE_LIMIT);
+        let total: RFPId = self.rfps.len().try_into().unwrap();
+        let start = cursor.unwrap_or(0).min(total);
+        let end = start.saturating_add(limit).min(total);
+        RFPSummariesPage {
+            rfps: self.get_rfp_summaries((start..end).collect()),
+            next_cursor: if end < total { Some(end) } else { None },
+        }
+    }
+
     #[payable]
     pub fn add_proposal(
         &mut self,
@@ -149,6 +391,7 @@ impl Contract {
             require!(labels.is_empty(), "Cannot add custom labels to this proposal. It inherits labels from the linked RFP. You should not add any labels to this proposal manually");
         }
 
+        self.assert_labels_not_deprecated(&labels, &HashSet::new());
         let labels = self.update_and_check_rfp_link(id, body.clone(), None, labels);
 
         require!(
@@ -159,7 +402,11 @@ impl Contract {
             "Cannot use these labels"
         );
 
-        require!(self.proposal_categories.contains(&proposal_body.category), "Unknown category");
+        self.assert_category_allows(&proposal_body, None);
+        let body = self.apply_category_defaults(body);
+        let proposal_body = body.clone().latest_version();
+        self.assert_not_duplicate(&proposal_body);
+        proposal_body.assert_new_milestones();
 
         let timeline = proposal_body.timeline.clone().latest_version();
 
@@ -178,6 +425,11 @@ impl Contract {
         author_proposals.insert(id);
         self.author_proposals.insert(&author_id, &author_proposals);
 
+        if timeline.is_review() {
+            self.open_voting_round(id);
+            self.open_review_round(id);
+        }
+
         let proposal = Proposal {
             id: id,
             author_id: author_id.clone(),
@@ -188,7 +440,7 @@ impl Contract {
                 labels,
                 body: body.clone(),
             },
-            snapshot_history: vec![],
+            revision: 0,
         };
 
         proposal::repost::publish_to_socialdb_feed(
@@ -213,10 +465,15 @@ impl Contract {
             "The account is not allowed to create RFPs"
         );
 
+        if let Some(budget) = &rfp_body.budget {
+            budget.assert_valid();
+        }
+
         require!(
             rfp_body.timeline.is_accepting_submissions(),
             "Cannot create proposal which is not in a Accepting Submissions state"
         );
+        self.assert_labels_not_deprecated(&labels, &HashSet::new());
 
         for label in &labels {
             require!(
@@ -241,7 +498,8 @@ impl Contract {
                 body: body.clone(),
                 linked_proposals: HashSet::new(),
             },
-            snapshot_history: vec![],
+            revision: 0,
+            legacy_block_heights: vec![],
         };
 
         proposal::repost::publish_to_socialdb_feed(
@@ -261,6 +519,8 @@ impl Contract {
     ) -> BlockHeightCallbackRetValue {
         proposal.social_db_post_block_height = set_result.block_height.into();
         self.proposals.push(&proposal.clone().into());
+        self.index_proposal_fingerprint(&proposal);
+        self.index_proposal_search(&proposal);
         BlockHeightCallbackRetValue { proposal_id: proposal.id }
     }
 
@@ -271,27 +531,50 @@ impl Contract {
     ) -> BlockHeightCallbackRetValue {
         let ret_value = BlockHeightCallbackRetValue { proposal_id: rfp.id };
         rfp.social_db_post_block_height = set_result.block_height.into();
+        self.index_rfp_search(&rfp);
         self.rfps.push(&rfp.into());
         ret_value
     }
 
+    /// Proposals written or co-authored by the account.
     pub fn get_proposals_by_author(&self, author: AccountId) -> Vec<ProposalId> {
-        self.author_proposals
-            .get(&author)
-            .map(|proposals| proposals.into_iter().collect())
-            .unwrap_or_default()
+        let mut proposals = self.author_proposals.get(&author).unwrap_or_default();
+        proposals.extend(self.coauthor_proposals.get(&author).unwrap_or_default());
+        proposals.into_iter().collect()
     }
 
-    pub fn get_proposals_by_label(&self, label: String) -> Vec<ProposalId> {
-        let mut res: Vec<_> =
-            self.label_to_proposals.get(&label).unwrap_or_default().into_iter().collect();
+    /// With `include_descendants`, also returns the proposals which have a registered descendant
+    /// of the label.
+    pub fn get_proposals_by_label(
+        &self,
+        label: String,
+        include_descendants: Option<bool>,
+    ) -> Vec<ProposalId> {
+        let mut labels = vec![label.clone()];
+        if include_descendants.unwrap_or(false) {
+            labels.extend(self.get_label_descendants(label));
+        }
+        let mut res: Vec<_> = labels
+            .iter()
+            .flat_map(|label| self.label_to_proposals.get(label).unwrap_or_default())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
         res.sort();
         res
     }
 
-    pub fn get_rfps_by_label(&self, label: String) -> Vec<RFPId> {
-        let mut res: Vec<_> =
-            self.label_to_rfps.get(&label).unwrap_or_default().into_iter().collect();
+    pub fn get_rfps_by_label(&self, label: String, include_descendants: Option<bool>) -> Vec<RFPId> {
+        let mut labels = vec![label.clone()];
+        if include_descendants.unwrap_or(false) {
+            labels.extend(self.get_label_descendants(label));
+        }
+        let mut res: Vec<_> = labels
+            .iter()
+            .flat_map(|label| self.label_to_rfps.get(label).unwrap_or_default())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
         res.sort();
         res
     }
@@ -326,7 +609,10 @@ impl Contract {
             .into();
         let editor = editor.unwrap_or_else(env::predecessor_account_id);
         // First check for simple cases.
-        if editor == env::current_account_id() || editor == proposal.author_id {
+        if editor == env::current_account_id()
+            || editor == proposal.author_id
+            || self.is_proposal_coauthor(proposal_id, &editor)
+        {
             return true;
         }
 
@@ -449,6 +735,8 @@ impl Contract {
         if proposal_body.linked_rfp.is_some() {
             require!(labels.len() == 0, "Cannot edit labels of a proposal linked to RFP. It inherits labels from the linked RFP.");
         }
+        let proposal: Proposal = self.get_proposal(id).into();
+        self.assert_labels_not_deprecated(&labels, &proposal.snapshot.labels);
         self.edit_proposal_internal(id, body.clone(), labels)
     }
 
@@ -483,6 +771,52 @@ impl Contract {
         self.edit_proposal_internal(id, body.into(), proposal.snapshot.labels)
     }
 
+    /// Ticks `sponsor_requested_review` of the proposal under review. Only the requested sponsor
+    /// of the proposal can call it.
+    #[payable]
+    pub fn request_review(&mut self, proposal_id: ProposalId) -> ProposalId {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        let mut body = proposal.snapshot.body.clone().latest_version();
+        require!(
+            body.requested_sponsor == env::predecessor_account_id(),
+            "Only the requested sponsor can request a review of this proposal"
+        );
+        let mut timeline = body.timeline.latest_version();
+        require!(timeline.is_review(), "The proposal is not under review");
+        timeline
+            .get_review_status_mut()
+            .unwrap()
+            .request_review(env::predecessor_account_id(), env::block_timestamp());
+        body.timeline = timeline.into();
+
+        let authors = self.save_proposal_edit(proposal_id, body.into(), proposal.snapshot.labels);
+        notify::notify_review_requested(&self.get_proposal(proposal_id).into(), authors);
+        proposal_id
+    }
+
+    /// Ticks `reviewer_completed_attestation` of the proposal under review. Only the supervisor of
+    /// the proposal can call it.
+    #[payable]
+    pub fn attest_review(&mut self, proposal_id: ProposalId) -> ProposalId {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        let mut body = proposal.snapshot.body.clone().latest_version();
+        require!(
+            body.supervisor == Some(env::predecessor_account_id()),
+            "Only the supervisor can attest the review of this proposal"
+        );
+        let mut timeline = body.timeline.latest_version();
+        require!(timeline.is_review(), "The proposal is not under review");
+        timeline
+            .get_review_status_mut()
+            .unwrap()
+            .attest(env::predecessor_account_id(), env::block_timestamp());
+        body.timeline = timeline.into();
+
+        let authors = self.save_proposal_edit(proposal_id, body.into(), proposal.snapshot.labels);
+        notify::notify_review_attested(&self.get_proposal(proposal_id).into(), authors);
+        proposal_id
+    }
+
     #[payable]
     pub fn edit_proposal_linked_rfp(&mut self, id: ProposalId, rfp_id: Option<RFPId>) -> ProposalId {
         let proposal: Proposal = self
@@ -503,6 +837,8 @@ impl Contract {
         body: VersionedRFPBody,
         labels: HashSet<String>,
     ) -> RFPId {
+        let rfp = self.load_rfp(id);
+        self.assert_labels_not_deprecated(&labels, &rfp.snapshot.labels);
         self.edit_rfp_internal(id, body.clone(), labels)
     }
 
@@ -522,17 +858,94 @@ impl Contract {
         self.edit_rfp_timeline(id, RFPTimelineStatus::Cancelled)
     }
 
+    /// Can be called by anyone. Returns the ids of the RFPs moved to Evaluation and the cursor to
+    /// continue from.
+    pub fn close_expired_rfps(&mut self, cursor: Option<RFPId>, limit: Option<u32>) -> ClosedRFPsPage {
+        let (closed, next_cursor) = self.close_expired_rfps_internal(
+            cursor.unwrap_or(0),
+            limit.unwrap_or(proposal::query::DEFAULT_PAGE_LIMIT),
+        );
+        ClosedRFPsPage { closed, next_cursor }
+    }
+
+    /// Returns `None` for the RFPs without a budget.
+    pub fn get_rfp_remaining_budget(&self, rfp_id: RFPId) -> Option<RFPRemainingBudget> {
+        self.get_rfp_remaining_budget_internal(rfp_id)
+    }
+
     #[payable]
     pub fn edit_rfp_timeline(&mut self, id: RFPId, timeline: RFPTimelineStatus) -> ProposalId {
-        let rfp: RFP = self.get_rfp(id).into();
+        let rfp = self.load_rfp(id);
         let mut body = rfp.snapshot.body.latest_version();
         body.timeline = timeline;
 
         self.edit_rfp_internal(id, body.into(), rfp.snapshot.labels)
     }
 
+    /// The attached deposit has to cover the storage of the comment, the rest is refunded.
+    #[payable]
+    pub fn add_comment(
+        &mut self,
+        target: CommentTarget,
+        parent_comment: Option<CommentId>,
+        body: String,
+    ) -> CommentId {
+        self.add_comment_internal(target, parent_comment, body)
+    }
+
+    /// The attached deposit has to cover the storage of the previous snapshot, the rest is
+    /// refunded.
+    #[payable]
+    pub fn edit_comment(&mut self, id: CommentId, body: String) -> CommentId {
+        self.edit_comment_internal(id, body)
+    }
+
+    pub fn delete_comment(&mut self, id: CommentId) -> CommentId {
+        self.delete_comment_internal(id)
+    }
+
+    pub fn get_comment(&self, id: CommentId) -> VersionedComment {
+        self.comments.get(id).unwrap_or_else(|| panic!("Comment id {} not found", id))
+    }
+
+    pub fn get_comment_revision(&self, id: CommentId, revision: CommentRevision) -> CommentSnapshot {
+        self.get_comment_snapshot(id, revision)
+            .unwrap_or_else(|| panic!("Revision {} of comment id {} not found", revision, id))
+    }
+
+    /// Returns the comments which start a thread on the target, oldest first.
+    pub fn get_comments(
+        &self,
+        target: CommentTarget,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> CommentsPage {
+        let total = self.comment_thread_counts.get(&target).unwrap_or(0);
+        self.get_comments_page(total, |index| self.comment_threads.get(&(target, index)), cursor, limit)
+    }
+
+    pub fn get_comment_replies(
+        &self,
+        comment_id: CommentId,
+        cursor: Option<u32>,
+        limit: Option<u32>,
+    ) -> CommentsPage {
+        let total = self.comment_reply_counts.get(&comment_id).unwrap_or(0);
+        self.get_comments_page(
+            total,
+            |index| self.comment_replies.get(&(comment_id, index)),
+            cursor,
+            limit,
+        )
+    }
+
+    /// Names of the active categories.
     pub fn get_allowed_categories(&self) -> Vec<String> {
-        self.proposal_categories.clone()
+        self.proposal_categories
+            .iter()
+            .filter(|category| category.active)
+            .map(|category| category.name.clone())
+            .collect()
     }
 
     pub fn get_global_labels(&self) -> Vec<LabelInfoExtended> {
@@ -542,6 +955,8 @@ impl Contract {
                 value: label.clone(),
                 title: label_info.title.clone(),
                 color: label_info.color.clone(),
+                parent: label_info.parent.clone(),
+                description: label_info.description.clone(),
             })
             .collect();
         result.sort_by(|a, b| a.value.cmp(&b.value));
@@ -564,10 +979,33 @@ impl Contract {
 
         self.global_labels_info.clear();
 
+        let values: HashSet<&str> = labels.iter().map(|label| label.value.as_str()).collect();
+        for label in &labels {
+            let parent = labels::parent_label(&label.value);
+            if let Some(declared_parent) = &label.parent {
+                require!(
+                    Some(declared_parent.as_str()) == parent,
+                    format!("Label {} is not a child of {}", label.value, declared_parent)
+                );
+            }
+            if let Some(parent) = parent {
+                require!(
+                    values.contains(parent),
+                    format!("Parent label {} of {} is not registered", parent, label.value)
+                );
+            }
+        }
         for label in labels {
-            let label_info = LabelInfo { title: label.title, color: label.color };
+            let label_info = LabelInfo {
+                title: label.title,
+                color: label.color,
+                parent: labels::parent_label(&label.value).map(str::to_string),
+                description: label.description,
+            };
             (*self.global_labels_info).insert(label.value, label_info);
         }
+        let global_labels_info = &self.global_labels_info;
+        self.deprecated_labels.retain(|label| global_labels_info.contains_key(label));
     }
 
     #[payable]
@@ -577,7 +1015,15 @@ impl Contract {
             self.has_moderator(editor_id.clone()) || editor_id.clone() == env::current_account_id(),
             "Only the admin and moderators can set categories"
         );
-        self.proposal_categories = new_categories;
+        // The records of the other categories are kept for their existing proposals.
+        for category in self.proposal_categories.iter_mut() {
+            category.active = new_categories.contains(&category.name);
+        }
+        for name in new_categories {
+            if self.get_category(&name).is_none() {
+                self.proposal_categories.push(ProposalCategory::new(name));
+            }
+        }
     }
 
     pub fn get_community(&self, handle: CommunityHandle) -> Option<Community> {
@@ -905,6 +1351,9 @@ impl Contract {
 pub struct LabelInfo {
     title: Option<String>,
     color: Option<(u8, u8, u8)>,
+    /// The label `parent/child` is a child of the label `parent`.
+    parent: Option<String>,
+    description: Option<String>,
 }
 
 #[near(serializers=[borsh, json])]
@@ -912,6 +1361,11 @@ pub struct LabelInfoExtended {
     value: String,
     title: Option<String>,
     color: Option<(u8, u8, u8)>,
+    /// Derived from `value` when omitted.
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, NearSchema)]
@@ -924,7 +1378,12 @@ pub struct BlockHeightCallbackRetValue {
 mod tests {
     use crate::community::AddOn;
 
-    use crate::{ProposalBodyV0, VersionedProposalBody};
+    use crate::proposal::timeline::TimelineStatusKind;
+    use crate::rfp::{RFPBodyV0, RFPBodyV1, RFPSnapshot, RFPV0, VersionedRFP, TimelineStatus as RFPTimelineStatus, VersionedRFPBody, RFP};
+    use crate::{
+        Proposal, ProposalBodyV0, ProposalFilters, ProposalSnapshot, ProposalV0,
+        VersionedProposal, VersionedProposalBody,
+    };
 
     use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
     use near_sdk::{testing_env, VMContext};
@@ -995,6 +1454,372 @@ mod tests {
         }
     }
 
+    fn review_proposal() -> VersionedProposal {
+        let body: ProposalBodyV0 = near_sdk::serde_json::from_value(json!({
+            "name": "proposal",
+            "description": "description",
+            "category": "Marketing",
+            "summary": "sum",
+            "linked_proposals": [],
+            "requested_sponsorship_usd_amount": "1000",
+            "requested_sponsorship_paid_in_currency": "USDT",
+            "receiver_account": "alice.near",
+            "supervisor": "frol.near",
+            "requested_sponsor": "neardevdao.near",
+            "timeline": {"status": "REVIEW", "sponsor_requested_review": false, "reviewer_completed_attestation": false}
+        }))
+        .unwrap();
+        VersionedProposal::V1(Proposal {
+            id: 0,
+            author_id: "alice.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot: ProposalSnapshot {
+                editor_id: "alice.near".parse().unwrap(),
+                timestamp: 0,
+                labels: HashSet::new(),
+                body: VersionedProposalBody::V0(body),
+            },
+            revision: 0,
+        })
+    }
+
+    #[test]
+    pub fn test_proposal_timeline_roles() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        contract.proposals.push(&review_proposal());
+
+        let allowed = contract.get_allowed_transitions(0, "alice.near".parse().unwrap());
+        assert_eq!(allowed, vec![TimelineStatusKind::Cancelled]);
+        let allowed = contract.get_allowed_transitions(0, "frol.near".parse().unwrap());
+        assert_eq!(allowed, vec![TimelineStatusKind::Review]);
+
+        testing_env!(get_context_with_predecessor(false, "neardevdao.near".to_string()));
+        contract.edit_proposal_timeline(
+            0,
+            near_sdk::serde_json::from_value(json!({
+                "status": "REVIEW", "sponsor_requested_review": true, "reviewer_completed_attestation": false
+            }))
+            .unwrap(),
+        );
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.revision, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transition from REVIEW to APPROVED is not allowed for this account")]
+    pub fn test_proposal_timeline_disallowed_edge() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        contract.proposals.push(&review_proposal());
+
+        testing_env!(get_context_with_predecessor(false, "frol.near".to_string()));
+        contract.edit_proposal_timeline(
+            0,
+            near_sdk::serde_json::from_value(json!({
+                "status": "APPROVED", "sponsor_requested_review": true, "reviewer_completed_attestation": true
+            }))
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    pub fn test_close_expired_rfps() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        contract.proposals.push(&review_proposal());
+        let body: RFPBodyV0 = near_sdk::serde_json::from_value(json!({
+            "name": "rfp",
+            "summary": "sum",
+            "description": "description",
+            "submission_deadline": "100",
+            "timeline": {"status": "ACCEPTING_SUBMISSIONS"}
+        }))
+        .unwrap();
+        contract.rfps.push(
+            &RFP {
+                id: 0,
+                author_id: "bob.near".parse().unwrap(),
+                social_db_post_block_height: 0,
+                snapshot: RFPSnapshot {
+                    editor_id: "bob.near".parse().unwrap(),
+                    timestamp: 0,
+                    block_height: 0,
+                    labels: HashSet::new(),
+                    body: VersionedRFPBody::V0(body),
+                    linked_proposals: [0].into(),
+                },
+                revision: 0,
+                legacy_block_heights: vec![],
+            }
+            .into(),
+        );
+
+        testing_env!(VMContextBuilder::new().block_timestamp(50).build());
+        assert!(contract.close_expired_rfps(None, None).closed.is_empty());
+        assert!(contract.get_rfp_summaries(vec![0])[0].timeline.is_accepting_submissions());
+
+        testing_env!(VMContextBuilder::new().block_timestamp(200).build());
+        let summary = contract.get_rfp_summaries(vec![0]).remove(0);
+        assert!(matches!(summary.timeline, RFPTimelineStatus::Evaluation));
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert!(matches!(rfp.snapshot.body.latest_version().timeline, RFPTimelineStatus::Evaluation));
+        assert_eq!(rfp.revision, 0);
+        let page = contract.close_expired_rfps(None, Some(10));
+        assert_eq!((page.closed, page.next_cursor), (vec![0], None));
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert!(matches!(rfp.snapshot.body.latest_version().timeline, RFPTimelineStatus::Evaluation));
+        assert_eq!(rfp.revision, 1);
+        assert!(contract.close_expired_rfps(None, None).closed.is_empty());
+    }
+
+    #[test]
+    pub fn test_rfp_revisions() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        let body: RFPBodyV0 = near_sdk::serde_json::from_value(json!({
+            "name": "rfp",
+            "summary": "sum",
+            "description": "description",
+            "submission_deadline": "100",
+            "timeline": {"status": "ACCEPTING_SUBMISSIONS"}
+        }))
+        .unwrap();
+        contract.rfps.push(&VersionedRFP::V0(RFPV0 {
+            id: 0,
+            author_id: "bob.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot: RFPSnapshot {
+                editor_id: "bob.near".parse().unwrap(),
+                timestamp: 0,
+                block_height: 20,
+                labels: HashSet::new(),
+                body: VersionedRFPBody::V0(body),
+                linked_proposals: HashSet::new(),
+            },
+            snapshot_history: vec![10],
+        }));
+
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert_eq!(rfp.revision, 0);
+        assert_eq!(rfp.legacy_block_heights, vec![10]);
+        assert_eq!(contract.get_rfp_revision(0, 0).block_height, 20);
+
+        contract.edit_rfp_timeline(0, RFPTimelineStatus::Evaluation);
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert_eq!(rfp.revision, 1);
+        assert_eq!(rfp.legacy_block_heights, vec![10]);
+        let history = contract.get_rfp_history(0, None, None);
+        assert_eq!(history.len(), 1);
+        assert!(history[0].body.clone().latest_version().timeline.is_accepting_submissions());
+        assert!(matches!(
+            contract.get_rfp_revision(0, 1).body.latest_version().timeline,
+            RFPTimelineStatus::Evaluation
+        ));
+    }
+
+    fn rfp_with_approved_proposal(contract: &mut Contract, max_usd_amount: &str, currency: &str) {
+        let mut proposal: Proposal = review_proposal().into();
+        proposal.snapshot.body = near_sdk::serde_json::from_value(json!({
+            "proposal_body_version": "V2",
+            "name": "proposal",
+            "description": "description",
+            "category": "Marketing",
+            "summary": "sum",
+            "linked_proposals": [],
+            "requested_sponsorship_usd_amount": "1000",
+            "requested_sponsorship_paid_in_currency": "USDC",
+            "receiver_account": "alice.near",
+            "supervisor": "frol.near",
+            "requested_sponsor": "neardevdao.near",
+            "linked_rfp": 0,
+            "timeline": {
+                "timeline_version": "V1", "status": "APPROVED", "sponsor_requested_review": true,
+                "reviewer_completed_attestation": true, "kyc_verified": true
+            }
+        }))
+        .unwrap();
+        contract.proposals.push(&proposal.into());
+        let body: RFPBodyV1 = near_sdk::serde_json::from_value(json!({
+            "name": "rfp",
+            "summary": "sum",
+            "description": "description",
+            "submission_deadline": "100",
+            "timeline": {"status": "EVALUATION"},
+            "budget": {
+                "min_usd_amount": "500", "max_usd_amount": max_usd_amount,
+                "currency": currency, "max_winners": 2
+            }
+        }))
+        .unwrap();
+        contract.rfps.push(
+            &RFP {
+                id: 0,
+                author_id: "bob.near".parse().unwrap(),
+                social_db_post_block_height: 0,
+                snapshot: RFPSnapshot {
+                    editor_id: "bob.near".parse().unwrap(),
+                    timestamp: 0,
+                    block_height: 0,
+                    labels: HashSet::new(),
+                    body: VersionedRFPBody::V1(body),
+                    linked_proposals: [0].into(),
+                },
+                revision: 0,
+                legacy_block_heights: vec![],
+            }
+            .into(),
+        );
+    }
+
+    fn proposal_selected(ids: &[u32]) -> RFPTimelineStatus {
+        near_sdk::serde_json::from_value(
+            json!({"status": "PROPOSAL_SELECTED", "selected_proposals": ids}),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    pub fn test_rfp_proposal_selected() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        rfp_with_approved_proposal(&mut contract, "3000", "USDC");
+
+        let remaining = contract.get_rfp_remaining_budget(0).unwrap();
+        assert_eq!(remaining.remaining_usd_amount, 3000);
+        contract.edit_rfp_timeline(0, proposal_selected(&[0]));
+
+        let rfp: RFP = contract.get_rfp(0).into();
+        assert_eq!(rfp.snapshot.body.latest_version().timeline.get_selected_proposals(), &[0]);
+        let remaining = contract.get_rfp_remaining_budget(0).unwrap();
+        assert_eq!(remaining.selected_usd_amount, 1000);
+        assert_eq!(remaining.remaining_usd_amount, 2000);
+        assert_eq!(remaining.remaining_winners, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The selected proposals request 1000 USD, over the budget of 800 USD")]
+    pub fn test_rfp_proposal_selected_over_budget() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        rfp_with_approved_proposal(&mut contract, "800", "USDC");
+        contract.edit_rfp_timeline(0, proposal_selected(&[0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal 0 is not paid in the currency of the RFP budget")]
+    pub fn test_rfp_proposal_selected_in_other_currency() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        rfp_with_approved_proposal(&mut contract, "3000", "NEAR");
+        contract.edit_rfp_timeline(0, proposal_selected(&[0]));
+    }
+
+    #[test]
+    pub fn test_review_sign_offs() {
+        testing_env!(get_context(false));
+        let mut contract = Contract::new();
+        contract.proposals.push(&review_proposal());
+
+        let mut context = get_context_with_predecessor(false, "neardevdao.near".to_string());
+        context.block_timestamp = 10;
+        testing_env!(context);
+        contract.request_review(0);
+
+        let mut context = get_context_with_predecessor(false, "frol.near".to_string());
+        context.block_timestamp = 20;
+        testing_env!(context);
+        contract.attest_review(0);
+
+        let timeline = |contract: &Contract| {
+            let proposal: Proposal = contract.get_proposal(0).into();
+            let timeline = proposal.snapshot.body.latest_version().timeline.latest_version();
+            near_sdk::serde_json::to_value(timeline).unwrap()
+        };
+        let value = timeline(&contract);
+        assert_eq!(value["sponsor_requested_review"], true);
+        assert_eq!(
+            value["review_requested_by"],
+            json!({"account_id": "neardevdao.near", "timestamp": "10"})
+        );
+        assert_eq!(value["attested_by"], json!({"account_id": "frol.near", "timestamp": "20"}));
+
+        // Sign-offs cannot be rewritten through a plain timeline edit.
+        testing_env!(get_context(false));
+        contract.edit_proposal_versioned_timeline(
+            0,
+            near_sdk::serde_json::from_value(json!({
+                "timeline_version": "V2", "status": "APPROVED", "sponsor_requested_review": true,
+                "reviewer_completed_attestation": false, "kyc_verified": false,
+                "review_requested_by": {"account_id": "alice.near", "timestamp": "0"}
+            }))
+            .unwrap(),
+        );
+        let value = timeline(&contract);
+        assert_eq!(value["review_requested_by"]["account_id"], "neardevdao.near");
+        assert_eq!(value["attested_by"], json!(null));
+    }
+
+    #[test]
+    pub fn test_proposal_revisions() {
+        let context = get_context(false);
+        testing_env!(context);
+        let mut contract = Contract::new();
+
+        let snapshot = |description: &str, timestamp: u64| {
+            let body: ProposalBodyV0 = near_sdk::serde_json::from_value(json!({
+                "name": "proposal",
+                "description": description,
+                "category": "Marketing",
+                "summary": "sum",
+                "linked_proposals": [],
+                "requested_sponsorship_usd_amount": "1000",
+                "requested_sponsorship_paid_in_currency": "USDT",
+                "receiver_account": "polyprogrammist.near",
+                "supervisor": "frol.near",
+                "requested_sponsor": "neardevdao.near",
+                "timeline": {"status": "DRAFT"}
+            }))
+            .unwrap();
+            ProposalSnapshot {
+                editor_id: "bob.near".parse().unwrap(),
+                timestamp,
+                labels: HashSet::new(),
+                body: VersionedProposalBody::V0(body),
+            }
+        };
+        let description = |snapshot: &ProposalSnapshot| {
+            snapshot.body.clone().latest_version().description
+        };
+        contract.proposals.push(&VersionedProposal::V0(ProposalV0 {
+            id: 0,
+            author_id: "bob.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot: snapshot("v2", 30),
+            snapshot_history: vec![snapshot("v0", 10), snapshot("v1", 20)],
+        }));
+
+        // Proposals in the old layout are served from the inline history.
+        assert_eq!(description(&contract.get_proposal_revision(0, 1)), "v1");
+        let history = contract.get_proposal_history(0, Some(1), None);
+        assert_eq!(history.iter().map(description).collect::<Vec<_>>(), vec!["v1"]);
+        let page = contract.get_proposals_page(None, None, Some(ProposalFilters {
+            created_before: Some(15.into()),
+            ..Default::default()
+        }));
+        assert_eq!(page.proposals.len(), 1);
+
+        contract.edit_proposal(0, snapshot("v3", 40).body, HashSet::new());
+
+        let proposal = contract.proposals.get(0).unwrap();
+        assert!(matches!(proposal, VersionedProposal::V1(Proposal { revision: 3, .. })));
+        assert_eq!(contract.proposal_revisions.get(&(0, 2)).map(|s| s.timestamp), Some(30));
+        let history = contract.get_proposal_history(0, None, Some(10));
+        assert_eq!(history.iter().map(description).collect::<Vec<_>>(), vec!["v0", "v1", "v2"]);
+        assert_eq!(description(&contract.get_proposal_revision(0, 3)), "v3");
+        assert_eq!(contract.get_proposal_summaries(vec![0])[0].history_length, 3);
+    }
+
     #[test]
     pub fn test_create_addon() {
         let context = get_context_with_current(false, "bob.near".to_string());
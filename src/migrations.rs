@@ -3,7 +3,7 @@
 //! latter is not asserted.
 
 use crate::*;
-use near_sdk::{borsh::to_vec, env, near, NearToken, Promise};
+use near_sdk::{borsh::to_vec, env, near, Gas, NearToken, Promise};
 use near_sdk::store::Lazy;
 use std::collections::{HashSet, HashMap};
 
@@ -603,7 +603,7 @@ impl Contract {
             proposals: Vector::new(StorageKey::Proposals),
             label_to_proposals: UnorderedMap::new(StorageKey::LabelToProposals),
             author_proposals: UnorderedMap::new(StorageKey::AuthorProposals),
-            proposal_categories: default_categories(),
+            proposal_categories: default_categories().into_iter().map(|category| category.name).collect(),
             communities,
             featured_communities,
             available_addons,
@@ -690,6 +690,2159 @@ pub struct ContractV11 {
     pub available_addons: UnorderedMap<AddOnId, AddOn>,
 }
 
+// From ContractV11 to ContractV12
+impl Contract {
+    fn unsafe_add_proposal_revisions() {
+        let ContractV11 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            label_to_proposals,
+            author_proposals,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV12 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions: LookupMap::new(StorageKey::ProposalRevisions),
+            label_to_proposals,
+            author_proposals,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+
+    /// Moves the inline snapshot history of proposals into `proposal_revisions`, starting from
+    /// proposal `migrated_count` and stopping when the gas runs low. Returns whether all the
+    /// proposals are migrated and the number of proposals processed so far.
+    fn unsafe_move_proposal_history(mut migrated_count: u64) -> (bool, u64) {
+        let mut contract: ContractV12 = env::state_read().unwrap();
+        while migrated_count < contract.proposals.len()
+            && env::prepaid_gas().saturating_sub(env::used_gas()) > MIGRATION_GAS_RESERVE
+        {
+            if let VersionedProposal::V0(proposal) = contract.proposals.get(migrated_count).unwrap()
+            {
+                for (revision, snapshot) in proposal.snapshot_history.iter().enumerate() {
+                    contract
+                        .proposal_revisions
+                        .insert(&(proposal.id, revision.try_into().unwrap()), snapshot);
+                }
+                contract.proposals.replace(migrated_count, &Proposal::from(proposal).into());
+            }
+            migrated_count += 1;
+        }
+        (migrated_count == contract.proposals.len(), migrated_count)
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV12 {
+    pub posts: Vector<VersionedPost>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV12 to ContractV13
+impl Contract {
+    fn unsafe_add_payout_transactions() {
+        let ContractV12 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV13 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions: LookupMap::new(StorageKey::PayoutTransactions),
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV13 {
+    pub posts: Vector<VersionedPost>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV13 to ContractV14
+impl Contract {
+    fn unsafe_add_comments() {
+        let ContractV13 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV14 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            comments: Vector::new(StorageKey::CommentsV2),
+            comment_revisions: LookupMap::new(StorageKey::CommentRevisions),
+            comment_threads: LookupMap::new(StorageKey::CommentThreads),
+            comment_thread_counts: LookupMap::new(StorageKey::CommentThreadCounts),
+            comment_replies: LookupMap::new(StorageKey::CommentReplies),
+            comment_reply_counts: LookupMap::new(StorageKey::CommentReplyCounts),
+            comment_counts: LookupMap::new(StorageKey::CommentCounts),
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV14 {
+    pub posts: Vector<VersionedPost>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV14 to ContractV15
+impl Contract {
+    fn unsafe_add_post_revisions() {
+        let ContractV14 {
+            posts,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV15 {
+            posts,
+            post_revisions: LookupMap::new(StorageKey::PostRevisions),
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV15 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV15 to ContractV16
+impl Contract {
+    fn unsafe_add_rfp_revisions() {
+        let ContractV15 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV16 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions: LookupMap::new(StorageKey::RFPRevisions),
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+
+    /// Rewrites the RFPs stored in the old layout, making their current snapshot revision 0.
+    fn unsafe_seed_rfp_revisions(mut migrated_count: u64) -> (bool, u64) {
+        let mut contract: ContractV16 = env::state_read().unwrap();
+        while migrated_count < contract.rfps.len()
+            && env::prepaid_gas().saturating_sub(env::used_gas()) > MIGRATION_GAS_RESERVE
+        {
+            if let VersionedRFP::V0(rfp) = contract.rfps.get(migrated_count).unwrap() {
+                contract.rfps.replace(migrated_count, &RFP::from(rfp).into());
+            }
+            migrated_count += 1;
+        }
+        (migrated_count == contract.rfps.len(), migrated_count)
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV16 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV16 to ContractV17
+impl Contract {
+    fn unsafe_add_rfp_questions() {
+        let ContractV16 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV17 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions: LookupMap::new(StorageKey::RFPQuestions),
+            rfp_question_counts: LookupMap::new(StorageKey::RFPQuestionCounts),
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV17 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV17 to ContractV18
+impl Contract {
+    fn unsafe_add_rfp_templates() {
+        let ContractV17 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV18 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates: Vector::new(StorageKey::RFPTemplates),
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV18 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV18 to ContractV19
+impl Contract {
+    fn unsafe_add_proposal_voting() {
+        let ContractV18 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV19 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies: UnorderedMap::new(StorageKey::VotingPolicies),
+            proposal_votes: LookupMap::new(StorageKey::ProposalVotes),
+            account_votes: LookupMap::new(StorageKey::AccountVotes),
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV19 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV19 to ContractV20
+impl Contract {
+    fn unsafe_add_proposal_reviews() {
+        let ContractV19 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV20 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies: UnorderedMap::new(StorageKey::ReviewPolicies),
+            proposal_reviews: LookupMap::new(StorageKey::ProposalReviews),
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV20 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV20 to ContractV21
+impl Contract {
+    fn unsafe_add_proposal_conflicts() {
+        let ContractV20 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV21 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts: LookupMap::new(StorageKey::ProposalConflicts),
+            account_conflicts: LookupMap::new(StorageKey::AccountConflicts),
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV21 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV21 to ContractV22
+impl Contract {
+    fn unsafe_add_proposal_coauthors() {
+        let ContractV21 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV22 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors: LookupMap::new(StorageKey::ProposalCoauthors),
+            coauthor_proposals: LookupMap::new(StorageKey::CoauthorProposals),
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV22 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV22 to ContractV23
+impl Contract {
+    fn unsafe_add_proposal_transfers() {
+        let ContractV22 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV23 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers: LookupMap::new(StorageKey::ProposalTransfers),
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV23 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV23 to ContractV24
+impl Contract {
+    fn unsafe_add_proposal_fingerprints() {
+        let ContractV23 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV24 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints: LookupMap::new(StorageKey::ProposalFingerprints),
+            fingerprint_proposals: LookupMap::new(StorageKey::FingerprintProposals),
+            shingle_proposals: LookupMap::new(StorageKey::ShingleProposals),
+            duplicate_window: None,
+            fingerprint_backfill: FingerprintBackfill::default(),
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV24 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV24 to ContractV25
+impl Contract {
+    fn unsafe_add_search_index() {
+        let ContractV24 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV25 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index: LookupMap::new(StorageKey::ProposalSearchIndex),
+            rfp_search_index: LookupMap::new(StorageKey::RFPSearchIndex),
+            search_terms: LookupMap::new(StorageKey::SearchTerms),
+            search_backfill: SearchBackfill::default(),
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV25 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub proposal_search_index: LookupMap<String, HashMap<ProposalId, u32>>,
+    pub rfp_search_index: LookupMap<String, HashMap<RFPId, u32>>,
+    pub search_terms: LookupMap<SearchDocument, Vec<String>>,
+    pub search_backfill: SearchBackfill,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV25 to ContractV26
+impl Contract {
+    fn unsafe_add_label_lifecycle() {
+        let ContractV25 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV26 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            deprecated_labels: HashSet::new(),
+            label_migrations: Vec::new(),
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV26 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<String>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub proposal_search_index: LookupMap<String, HashMap<ProposalId, u32>>,
+    pub rfp_search_index: LookupMap<String, HashMap<RFPId, u32>>,
+    pub search_terms: LookupMap<SearchDocument, Vec<String>>,
+    pub search_backfill: SearchBackfill,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub deprecated_labels: HashSet<String>,
+    pub label_migrations: Vec<LabelMigration>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV26 to ContractV27
+impl Contract {
+    fn unsafe_structure_labels_and_categories() {
+        let ContractV26 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            deprecated_labels,
+            label_migrations,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        // The labels are stored apart from the contract state by their `Lazy`.
+        let labels_key = to_vec(&StorageKey::LabelInfo).unwrap();
+        if let Some(bytes) = env::storage_read(&labels_key) {
+            let labels: HashMap<String, LabelInfoV0> = near_sdk::borsh::from_slice(&bytes).unwrap();
+            let labels: HashMap<String, LabelInfo> = labels
+                .iter()
+                .map(|(value, info)| {
+                    let parent = labels::parent_label(value)
+                        .filter(|parent| labels.contains_key(*parent))
+                        .map(str::to_string);
+                    let info = LabelInfo {
+                        title: info.title.clone(),
+                        color: info.color,
+                        parent,
+                        description: None,
+                    };
+                    (value.clone(), info)
+                })
+                .collect();
+            env::storage_write(&labels_key, &to_vec(&labels).unwrap());
+        }
+
+        env::state_write(&ContractV27 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories: proposal_categories.into_iter().map(ProposalCategoryV0::new).collect(),
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            deprecated_labels,
+            label_migrations,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV27 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<ProposalCategoryV0>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub proposal_search_index: LookupMap<String, HashMap<ProposalId, u32>>,
+    pub rfp_search_index: LookupMap<String, HashMap<RFPId, u32>>,
+    pub search_terms: LookupMap<SearchDocument, Vec<String>>,
+    pub search_backfill: SearchBackfill,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub deprecated_labels: HashSet<String>,
+    pub label_migrations: Vec<LabelMigration>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+// From ContractV27 to ContractV28
+impl Contract {
+    fn unsafe_add_category_budgets() {
+        let ContractV27 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories,
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            deprecated_labels,
+            label_migrations,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        } = env::state_read().unwrap();
+
+        env::state_write(&ContractV28 {
+            posts,
+            post_revisions,
+            post_to_parent,
+            post_to_children,
+            label_to_posts,
+            access_control,
+            authors,
+            proposals,
+            proposal_revisions,
+            label_to_proposals,
+            author_proposals,
+            payout_transactions,
+            proposal_coauthors,
+            coauthor_proposals,
+            proposal_transfers,
+            proposal_fingerprints,
+            fingerprint_proposals,
+            shingle_proposals,
+            duplicate_window,
+            fingerprint_backfill,
+            proposal_categories: proposal_categories.into_iter().map(Into::into).collect(),
+            proposal_spending: LookupMap::new(StorageKey::ProposalSpending),
+            budget_overrides: LookupMap::new(StorageKey::BudgetOverrides),
+            voting_policies,
+            proposal_votes,
+            account_votes,
+            review_policies,
+            proposal_reviews,
+            proposal_conflicts,
+            account_conflicts,
+            rfps,
+            label_to_rfps,
+            rfp_revisions,
+            rfp_questions,
+            rfp_question_counts,
+            rfp_templates,
+            proposal_search_index,
+            rfp_search_index,
+            search_terms,
+            search_backfill,
+            global_labels_info,
+            deprecated_labels,
+            label_migrations,
+            comments,
+            comment_revisions,
+            comment_threads,
+            comment_thread_counts,
+            comment_replies,
+            comment_reply_counts,
+            comment_counts,
+            communities,
+            featured_communities,
+            available_addons,
+        });
+    }
+}
+
+#[near]
+#[derive(PanicOnDefault)]
+pub struct ContractV28 {
+    pub posts: Vector<VersionedPost>,
+    pub post_revisions: LookupMap<(PostId, PostRevision), PostSnapshot>,
+    pub post_to_parent: LookupMap<PostId, PostId>,
+    pub post_to_children: LookupMap<PostId, Vec<PostId>>,
+    pub label_to_posts: UnorderedMap<String, HashSet<PostId>>,
+    pub access_control: AccessControl,
+    pub authors: UnorderedMap<AccountId, HashSet<PostId>>,
+    pub proposals: Vector<VersionedProposal>,
+    pub proposal_revisions: LookupMap<(ProposalId, ProposalRevision), ProposalSnapshot>,
+    pub label_to_proposals: UnorderedMap<String, HashSet<ProposalId>>,
+    pub author_proposals: UnorderedMap<AccountId, HashSet<ProposalId>>,
+    pub payout_transactions: LookupMap<String, ProposalId>,
+    pub proposal_coauthors: LookupMap<ProposalId, HashSet<AccountId>>,
+    pub coauthor_proposals: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub proposal_transfers: LookupMap<ProposalId, ProposalOwnership>,
+    pub proposal_fingerprints: LookupMap<ProposalId, ProposalFingerprint>,
+    pub fingerprint_proposals: LookupMap<CryptoHash, Vec<ProposalId>>,
+    pub shingle_proposals: LookupMap<u64, Vec<ProposalId>>,
+    pub duplicate_window: Option<u64>,
+    pub fingerprint_backfill: FingerprintBackfill,
+    pub proposal_categories: Vec<ProposalCategory>,
+    pub proposal_spending: LookupMap<ProposalId, ProposalSpending>,
+    pub budget_overrides: LookupMap<ProposalId, AccountId>,
+    pub voting_policies: UnorderedMap<String, VotingPolicy>,
+    pub proposal_votes: LookupMap<ProposalId, ProposalVotes>,
+    pub account_votes: LookupMap<(ProposalId, AccountId), Vote>,
+    pub review_policies: UnorderedMap<String, ReviewPolicy>,
+    pub proposal_reviews: LookupMap<ProposalId, ProposalReviews>,
+    pub proposal_conflicts: LookupMap<ProposalId, Vec<ConflictDeclaration>>,
+    pub account_conflicts: LookupMap<AccountId, HashSet<ProposalId>>,
+    pub rfps: Vector<VersionedRFP>,
+    pub label_to_rfps: UnorderedMap<String, HashSet<RFPId>>,
+    pub rfp_revisions: LookupMap<(RFPId, RFPRevision), RFPSnapshot>,
+    pub rfp_questions: LookupMap<(RFPId, RFPQuestionId), RFPQuestion>,
+    pub rfp_question_counts: LookupMap<RFPId, RFPQuestionId>,
+    pub rfp_templates: Vector<RFPTemplate>,
+    pub proposal_search_index: LookupMap<String, HashMap<ProposalId, u32>>,
+    pub rfp_search_index: LookupMap<String, HashMap<RFPId, u32>>,
+    pub search_terms: LookupMap<SearchDocument, Vec<String>>,
+    pub search_backfill: SearchBackfill,
+    pub global_labels_info: Lazy<HashMap<String, LabelInfo>>,
+    pub deprecated_labels: HashSet<String>,
+    pub label_migrations: Vec<LabelMigration>,
+    pub comments: Vector<VersionedComment>,
+    pub comment_revisions: LookupMap<(CommentId, CommentRevision), CommentSnapshot>,
+    pub comment_threads: LookupMap<(CommentTarget, u32), CommentId>,
+    pub comment_thread_counts: LookupMap<CommentTarget, u32>,
+    pub comment_replies: LookupMap<(CommentId, u32), CommentId>,
+    pub comment_reply_counts: LookupMap<CommentId, u32>,
+    pub comment_counts: LookupMap<CommentTarget, u32>,
+    pub communities: UnorderedMap<CommunityHandle, CommunityV5>,
+    pub featured_communities: Vec<FeaturedCommunity>,
+    pub available_addons: UnorderedMap<AddOnId, AddOn>,
+}
+
+/// `ProposalCategory` before the category budgets.
+#[near(serializers=[borsh])]
+pub struct ProposalCategoryV0 {
+    pub name: String,
+    pub description: String,
+    pub default_supervisor: Option<AccountId>,
+    pub budget_cap: Option<near_sdk::json_types::U64>,
+    pub active: bool,
+}
+
+impl ProposalCategoryV0 {
+    fn new(name: String) -> Self {
+        ProposalCategoryV0 {
+            name,
+            description: String::new(),
+            default_supervisor: None,
+            budget_cap: None,
+            active: true,
+        }
+    }
+}
+
+impl From<ProposalCategoryV0> for ProposalCategory {
+    fn from(v0: ProposalCategoryV0) -> Self {
+        ProposalCategory {
+            name: v0.name,
+            description: v0.description,
+            default_supervisor: v0.default_supervisor,
+            budget_cap: v0.budget_cap,
+            active: v0.active,
+            budgets: vec![],
+        }
+    }
+}
+
+/// `LabelInfo` before the label hierarchy.
+#[near]
+pub struct LabelInfoV0 {
+    title: Option<String>,
+    color: Option<(u8, u8, u8)>,
+}
+
+/// Gas left untouched by a batch of a resumable migration so that the call can record its progress.
+const MIGRATION_GAS_RESERVE: Gas = Gas::from_tgas(20);
+
 #[near]
 #[derive(Debug)]
 pub(crate) enum StateVersion {
@@ -704,6 +2857,23 @@ pub(crate) enum StateVersion {
     V9,
     V10,
     V11,
+    V12 { done: bool, migrated_count: u64 },
+    V13,
+    V14,
+    V15,
+    V16 { done: bool, migrated_count: u64 },
+    V17,
+    V18,
+    V19,
+    V20,
+    V21,
+    V22,
+    V23,
+    V24,
+    V25,
+    V26,
+    V27,
+    V28,
 }
 
 const VERSION_KEY: &[u8] = b"VERSION";
@@ -796,6 +2966,82 @@ impl Contract {
                 Contract::unsafe_add_rfp();
                 state_version_write(&StateVersion::V11);
             }
+            StateVersion::V11 => {
+                Contract::unsafe_add_proposal_revisions();
+                state_version_write(&StateVersion::V12 { done: false, migrated_count: 0 });
+            }
+            StateVersion::V12 { done: false, migrated_count } => {
+                let (done, migrated_count) = Contract::unsafe_move_proposal_history(migrated_count);
+                state_version_write(&StateVersion::V12 { done, migrated_count });
+            }
+            StateVersion::V12 { done: true, migrated_count: _ } => {
+                Contract::unsafe_add_payout_transactions();
+                state_version_write(&StateVersion::V13);
+            }
+            StateVersion::V13 => {
+                Contract::unsafe_add_comments();
+                state_version_write(&StateVersion::V14);
+            }
+            StateVersion::V14 => {
+                Contract::unsafe_add_post_revisions();
+                state_version_write(&StateVersion::V15);
+            }
+            StateVersion::V15 => {
+                Contract::unsafe_add_rfp_revisions();
+                state_version_write(&StateVersion::V16 { done: false, migrated_count: 0 });
+            }
+            StateVersion::V16 { done: false, migrated_count } => {
+                let (done, migrated_count) = Contract::unsafe_seed_rfp_revisions(migrated_count);
+                state_version_write(&StateVersion::V16 { done, migrated_count });
+            }
+            StateVersion::V16 { done: true, migrated_count: _ } => {
+                Contract::unsafe_add_rfp_questions();
+                state_version_write(&StateVersion::V17);
+            }
+            StateVersion::V17 => {
+                Contract::unsafe_add_rfp_templates();
+                state_version_write(&StateVersion::V18);
+            }
+            StateVersion::V18 => {
+                Contract::unsafe_add_proposal_voting();
+                state_version_write(&StateVersion::V19);
+            }
+            StateVersion::V19 => {
+                Contract::unsafe_add_proposal_reviews();
+                state_version_write(&StateVersion::V20);
+            }
+            StateVersion::V20 => {
+                Contract::unsafe_add_proposal_conflicts();
+                state_version_write(&StateVersion::V21);
+            }
+            StateVersion::V21 => {
+                Contract::unsafe_add_proposal_coauthors();
+                state_version_write(&StateVersion::V22);
+            }
+            StateVersion::V22 => {
+                Contract::unsafe_add_proposal_transfers();
+                state_version_write(&StateVersion::V23);
+            }
+            StateVersion::V23 => {
+                Contract::unsafe_add_proposal_fingerprints();
+                state_version_write(&StateVersion::V24);
+            }
+            StateVersion::V24 => {
+                Contract::unsafe_add_search_index();
+                state_version_write(&StateVersion::V25);
+            }
+            StateVersion::V25 => {
+                Contract::unsafe_add_label_lifecycle();
+                state_version_write(&StateVersion::V26);
+            }
+            StateVersion::V26 => {
+                Contract::unsafe_structure_labels_and_categories();
+                state_version_write(&StateVersion::V27);
+            }
+            StateVersion::V27 => {
+                Contract::unsafe_add_category_budgets();
+                state_version_write(&StateVersion::V28);
+            }
             _ => {
                 return Contract::migration_done();
             }
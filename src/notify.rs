@@ -1,8 +1,10 @@
 use std::collections::HashSet;
 
 use crate::{
-    get_subscribers, rfp::get_subscribers as get_rfp_subscribers, Proposal, ProposalId, RFP,
+    get_subscribers, rfp::get_subscribers as get_rfp_subscribers, Proposal, ProposalId, RFPId,
+    RFP,
 };
+use crate::rfp::questions::RFPQuestionId;
 use devhub_common::social_db_contract;
 use near_sdk::serde_json::json;
 use near_sdk::{env, AccountId, Promise};
@@ -43,13 +45,21 @@ pub fn notify_accounts(
     accounts: Vec<String>,
     notify_value: serde_json::Value,
 ) -> Promise {
-    if !accounts.is_empty() {
+    notify_entries(
+        notifier,
+        accounts.into_iter().map(|account| (account, notify_value.clone())).collect(),
+    )
+}
+
+/// Sends every `(account, value)` notification with a single call to the social db.
+pub fn notify_entries(notifier: AccountId, entries: Vec<(String, serde_json::Value)>) -> Promise {
+    if !entries.is_empty() {
         let mut notify_values = Vec::new();
 
-        for account in accounts {
+        for (account, value) in entries {
             notify_values.push(json!({
                 "key": account,
-                "value": notify_value,
+                "value": value,
             }));
         }
 
@@ -102,31 +112,106 @@ pub fn notify_rfp_subscribers(rfp: &RFP, additional_accounts: HashSet<AccountId>
     )
 }
 
-pub fn notify_edit_proposal(proposal_id: ProposalId, post_author: AccountId) -> Promise {
-    notify(
+/// Notifies the authors of the proposals linked to each closed RFP.
+pub fn notify_rfps_evaluation(closed: Vec<(RFPId, HashSet<AccountId>)>) -> Promise {
+    let mut entries = vec![];
+    for (rfp_id, accounts) in closed {
+        for account in accounts {
+            entries.push((
+                account.to_string(),
+                json!({
+                    "type": "rfp/evaluation",
+                    "rfp": rfp_id,
+                    "widgetAccountId": env::current_account_id(),
+                    "notifier": env::current_account_id(),
+                }),
+            ));
+        }
+    }
+    notify_entries(env::current_account_id(), entries)
+}
+
+pub fn notify_rfp_answer(
+    rfp_id: RFPId,
+    question_id: RFPQuestionId,
+    accounts: HashSet<AccountId>,
+) -> Promise {
+    notify_accounts(
+        env::current_account_id(),
+        accounts.iter().map(|account| account.to_string()).collect(),
+        json!({
+            "type": "rfp/answer",
+            "rfp": rfp_id,
+            "question": question_id,
+            "widgetAccountId": env::current_account_id(),
+            "notifier": env::predecessor_account_id(),
+        }),
+    )
+}
+
+pub fn notify_edit_proposal(proposal_id: ProposalId, authors: Vec<AccountId>) -> Promise {
+    notify_entries(env::current_account_id(), edit_proposal_entries(proposal_id, authors))
+}
+
+fn edit_proposal_entries(
+    proposal_id: ProposalId,
+    authors: Vec<AccountId>,
+) -> Vec<(String, serde_json::Value)> {
+    let value = json!({
+        "type": "proposal/edit",
+        "proposal": proposal_id,
+        "widgetAccountId": env::current_account_id(),
+        "notifier": env::predecessor_account_id(),
+    });
+    authors.iter().map(|author| (author.to_string(), value.clone())).collect()
+}
+
+pub fn notify_proposal_transfer(
+    proposal_id: ProposalId,
+    previous_author: AccountId,
+    new_author: AccountId,
+) -> Promise {
+    notify_accounts(
         env::current_account_id(),
-        post_author,
+        vec![previous_author.to_string(), new_author.to_string()],
         json!({
-            "type": "proposal/edit",
+            "type": "proposal/transfer",
             "proposal": proposal_id,
+            "previous_author": previous_author,
+            "new_author": new_author,
             "widgetAccountId": env::current_account_id(),
             "notifier": env::predecessor_account_id(),
         }),
     )
 }
 
-fn notify(notifier: AccountId, post_author: AccountId, notify_value: serde_json::Value) -> Promise {
-    social_db_contract()
-        .with_static_gas(env::prepaid_gas().saturating_div(4))
-        .with_attached_deposit(env::attached_deposit())
-        .set(json!({
-            notifier : {
-                "index": {
-                    "notify": json!({
-                        "key": post_author,
-                        "value": notify_value,
-                    }).to_string()
-                }
-            }
-        }))
+/// Notifies the supervisor of the review request and the authors of the edit in a single call.
+pub fn notify_review_requested(proposal: &Proposal, authors: Vec<AccountId>) -> Promise {
+    let body = proposal.snapshot.body.clone().latest_version();
+    let value = json!({
+        "type": "proposal/review_requested",
+        "proposal": proposal.id,
+        "widgetAccountId": env::current_account_id(),
+        "notifier": env::predecessor_account_id(),
+    });
+    let mut entries = edit_proposal_entries(proposal.id, authors);
+    entries.extend(body.supervisor.iter().map(|supervisor| (supervisor.to_string(), value.clone())));
+    notify_entries(env::current_account_id(), entries)
+}
+
+/// Notifies the requested sponsor and the author of the attestation and the authors of the edit
+/// in a single call.
+pub fn notify_review_attested(proposal: &Proposal, authors: Vec<AccountId>) -> Promise {
+    let body = proposal.snapshot.body.clone().latest_version();
+    let value = json!({
+        "type": "proposal/review_attested",
+        "proposal": proposal.id,
+        "widgetAccountId": env::current_account_id(),
+        "notifier": env::predecessor_account_id(),
+    });
+    let mut entries = edit_proposal_entries(proposal.id, authors);
+    for account in [&body.requested_sponsor, &proposal.author_id] {
+        entries.push((account.to_string(), value.clone()));
+    }
+    notify_entries(env::current_account_id(), entries)
 }
@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use crate::access_control::members::ActionType;
+use crate::common::charge_storage_deposit;
+use crate::proposal::ProposalFundingCurrency;
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt, PostId, VersionedPost, ROOT_POST_ID};
+
+/// Number of a post snapshot. The first snapshot of a post has revision 0 and every edit
+/// increments it.
+pub type PostRevision = u32;
+
+/// Maximum number of characters of the name and the description of a post together.
+pub const MAX_POST_LENGTH: usize = 10000;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostType {
+    Comment,
+    Idea,
+    Solution,
+    Attestation,
+    Sponsorship,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct IdeaBody {
+    pub name: String,
+    pub description: String,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct SolutionBody {
+    pub name: String,
+    pub description: String,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct AttestationBody {
+    pub name: String,
+    pub description: String,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct SponsorshipBody {
+    pub name: String,
+    pub description: String,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub amount: u32,
+    pub currency: ProposalFundingCurrency,
+    pub supervisor: AccountId,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct CommentBody {
+    pub description: String,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "post_type")]
+pub enum PostBody {
+    Comment(CommentBody),
+    Idea(IdeaBody),
+    Solution(SolutionBody),
+    Attestation(AttestationBody),
+    Sponsorship(SponsorshipBody),
+}
+
+impl PostBody {
+    pub fn post_type(&self) -> PostType {
+        match self {
+            PostBody::Comment(_) => PostType::Comment,
+            PostBody::Idea(_) => PostType::Idea,
+            PostBody::Solution(_) => PostType::Solution,
+            PostBody::Attestation(_) => PostType::Attestation,
+            PostBody::Sponsorship(_) => PostType::Sponsorship,
+        }
+    }
+
+    /// Number of characters of the name and the description.
+    fn text_length(&self) -> usize {
+        let (name, description) = match self {
+            PostBody::Comment(body) => ("", &body.description),
+            PostBody::Idea(body) => (body.name.as_str(), &body.description),
+            PostBody::Solution(body) => (body.name.as_str(), &body.description),
+            PostBody::Attestation(body) => (body.name.as_str(), &body.description),
+            PostBody::Sponsorship(body) => (body.name.as_str(), &body.description),
+        };
+        name.chars().count() + description.chars().count()
+    }
+}
+
+impl PostType {
+    /// Whether a post of this type can be added under `parent`, `None` being the root of the
+    /// board. Comments can be added anywhere except the root.
+    pub fn can_have_parent(&self, parent: Option<PostType>) -> bool {
+        match self {
+            PostType::Idea => parent.is_none(),
+            PostType::Solution => matches!(parent, None | Some(PostType::Idea)),
+            PostType::Attestation | PostType::Sponsorship => parent == Some(PostType::Solution),
+            PostType::Comment => parent.is_some(),
+        }
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct PostSnapshot {
+    pub editor_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub labels: HashSet<String>,
+    #[serde(flatten)]
+    pub body: PostBody,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct Post {
+    pub id: PostId,
+    pub author_id: AccountId,
+    pub snapshot: PostSnapshot,
+    /// Revision of `snapshot`, which is also the number of previous snapshots. The previous
+    /// snapshots are kept in `post_revisions`.
+    pub revision: PostRevision,
+}
+
+impl From<VersionedPost> for Post {
+    fn from(vp: VersionedPost) -> Self {
+        match vp {
+            VersionedPost::V0 => env::panic_str("The post was stored without content"),
+            VersionedPost::V1(v1) => v1,
+        }
+    }
+}
+
+impl From<Post> for VersionedPost {
+    fn from(p: Post) -> Self {
+        VersionedPost::V1(p)
+    }
+}
+
+impl Contract {
+    fn get_post_internal(&self, id: PostId) -> Post {
+        self.posts.get(id).unwrap_or_else(|| panic!("Post id {} not found", id)).into()
+    }
+
+    fn update_post_labels(&mut self, id: PostId, old: &HashSet<String>, new: &HashSet<String>) {
+        for label in old.difference(new) {
+            let mut posts = self.label_to_posts.get(label).unwrap_or_default();
+            posts.remove(&id);
+            if posts.is_empty() {
+                self.label_to_posts.remove(label);
+            } else {
+                self.label_to_posts.insert(label, &posts);
+            }
+        }
+        for label in new.difference(old) {
+            let mut posts = self.label_to_posts.get(label).unwrap_or_default();
+            posts.insert(id);
+            self.label_to_posts.insert(label, &posts);
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    /// The attached deposit has to cover the storage of the post, the rest is refunded.
+    #[payable]
+    pub fn add_post(
+        &mut self,
+        parent_id: Option<PostId>,
+        body: PostBody,
+        labels: HashSet<String>,
+    ) -> PostId {
+        let author_id = env::predecessor_account_id();
+        let parent_type =
+            parent_id.map(|parent_id| self.get_post_internal(parent_id).snapshot.body.post_type());
+        require!(
+            body.post_type().can_have_parent(parent_type),
+            format!("{:?} cannot be added under this parent", body.post_type())
+        );
+        require!(
+            self.is_allowed_to_use_labels(
+                Some(author_id.clone()),
+                labels.iter().cloned().collect()
+            ),
+            "Cannot use these labels"
+        );
+        require_post_length(&body);
+
+        let initial_storage_usage = env::storage_usage();
+        let id = self.posts.len();
+        self.update_post_labels(id, &HashSet::new(), &labels);
+        let parent_id = parent_id.unwrap_or(ROOT_POST_ID);
+        self.post_to_parent.insert(&id, &parent_id);
+        let mut siblings = self.post_to_children.get(&parent_id).unwrap_or_default();
+        siblings.push(id);
+        self.post_to_children.insert(&parent_id, &siblings);
+        let mut author_posts = self.authors.get(&author_id).unwrap_or_default();
+        author_posts.insert(id);
+        self.authors.insert(&author_id, &author_posts);
+
+        let post = Post {
+            id,
+            author_id: author_id.clone(),
+            snapshot: PostSnapshot {
+                editor_id: author_id,
+                timestamp: env::block_timestamp(),
+                labels,
+                body,
+            },
+            revision: 0,
+        };
+        self.posts.push(&post.into());
+        charge_storage_deposit(initial_storage_usage);
+        id
+    }
+
+    /// The attached deposit has to cover the storage of the previous snapshot, the rest is
+    /// refunded.
+    #[payable]
+    pub fn edit_post(&mut self, id: PostId, body: PostBody, labels: HashSet<String>) -> PostId {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.is_allowed_to_edit_post(id, Some(editor_id.clone())),
+            "The account is not allowed to edit this post"
+        );
+        let mut post = self.get_post_internal(id);
+        require!(
+            post.snapshot.body.post_type() == body.post_type(),
+            "Cannot change the type of a post"
+        );
+        let old_labels = &post.snapshot.labels;
+        let changed_labels: Vec<String> =
+            old_labels.symmetric_difference(&labels).cloned().collect();
+        require!(
+            self.is_allowed_to_use_labels(Some(editor_id.clone()), changed_labels),
+            "Cannot use these labels"
+        );
+        require_post_length(&body);
+
+        let initial_storage_usage = env::storage_usage();
+        self.update_post_labels(id, &post.snapshot.labels.clone(), &labels);
+        let new_snapshot =
+            PostSnapshot { editor_id, timestamp: env::block_timestamp(), labels, body };
+        let old_snapshot = std::mem::replace(&mut post.snapshot, new_snapshot);
+        self.post_revisions.insert(&(id, post.revision), &old_snapshot);
+        post.revision += 1;
+        self.posts.replace(id, &post.into());
+        charge_storage_deposit(initial_storage_usage);
+        id
+    }
+
+    pub fn is_allowed_to_edit_post(&self, post_id: PostId, editor: Option<AccountId>) -> bool {
+        let post = self.get_post_internal(post_id);
+        let editor = editor.unwrap_or_else(env::predecessor_account_id);
+        if editor == env::current_account_id() || editor == post.author_id {
+            return true;
+        }
+        self.access_control
+            .members_list
+            .check_permissions(editor, post.snapshot.labels.into_iter().collect())
+            .contains(&ActionType::EditPost)
+    }
+
+    pub fn get_post(&self, post_id: PostId) -> VersionedPost {
+        self.posts.get(post_id).unwrap_or_else(|| panic!("Post id {} not found", post_id))
+    }
+
+    pub fn get_post_revision(&self, post_id: PostId, revision: PostRevision) -> PostSnapshot {
+        let post = self.get_post_internal(post_id);
+        if revision == post.revision {
+            return post.snapshot;
+        }
+        self.post_revisions
+            .get(&(post_id, revision))
+            .unwrap_or_else(|| panic!("Revision {} of post id {} not found", revision, post_id))
+    }
+
+    /// Returns the ids of the direct children of the post, or of the top posts when `parent_id`
+    /// is `None`.
+    pub fn get_posts_by_parent(&self, parent_id: Option<PostId>) -> Vec<PostId> {
+        self.post_to_children.get(&parent_id.unwrap_or(ROOT_POST_ID)).unwrap_or_default()
+    }
+
+    pub fn get_posts_by_label(&self, label: String) -> Vec<PostId> {
+        let mut res: Vec<_> =
+            self.label_to_posts.get(&label).unwrap_or_default().into_iter().collect();
+        res.sort();
+        res
+    }
+
+    pub fn get_posts_by_author(&self, author: AccountId) -> Vec<PostId> {
+        let mut res: Vec<_> = self.authors.get(&author).unwrap_or_default().into_iter().collect();
+        res.sort();
+        res
+    }
+
+    pub fn get_all_post_labels(&self) -> Vec<String> {
+        let mut res: Vec<_> = self.label_to_posts.keys().collect();
+        res.sort();
+        res
+    }
+}
+
+fn require_post_length(body: &PostBody) {
+    require!(
+        body.text_length() <= MAX_POST_LENGTH,
+        format!("The post cannot be longer than {} characters", MAX_POST_LENGTH)
+    );
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::NearToken;
+
+    use super::{Post, PostBody};
+    use crate::test_fixtures::set_deposit;
+    use crate::Contract;
+
+    const DEPOSIT: NearToken = NearToken::from_millinear(100);
+
+    fn body(value: serde_json::Value) -> PostBody {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn labels(labels: &[&str]) -> HashSet<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn builds_post_tree() {
+        set_deposit("devhub.near", DEPOSIT);
+        let mut contract = Contract::new();
+
+        set_deposit("alice.near", DEPOSIT);
+        let idea = contract.add_post(
+            None,
+            body(json!({"post_type": "Idea", "name": "Idea", "description": "Do it"})),
+            labels(&["a"]),
+        );
+        set_deposit("bob.near", DEPOSIT);
+        let solution = contract.add_post(
+            Some(idea),
+            body(json!({"post_type": "Solution", "name": "Solution", "description": "How"})),
+            labels(&["a", "b"]),
+        );
+        let comment = contract.add_post(
+            Some(solution),
+            body(json!({"post_type": "Comment", "description": "Nice"})),
+            labels(&[]),
+        );
+        contract.edit_post(
+            solution,
+            body(json!({"post_type": "Solution", "name": "Solution", "description": "How, v2"})),
+            labels(&["b"]),
+        );
+
+        assert_eq!(contract.get_posts_by_parent(None), vec![idea]);
+        assert_eq!(contract.get_posts_by_parent(Some(idea)), vec![solution]);
+        assert_eq!(contract.get_posts_by_parent(Some(solution)), vec![comment]);
+        assert_eq!(contract.get_posts_by_label("a".to_string()), vec![idea]);
+        assert_eq!(contract.get_posts_by_label("b".to_string()), vec![solution]);
+        assert_eq!(
+            contract.get_posts_by_author("bob.near".parse().unwrap()),
+            vec![solution, comment]
+        );
+        assert_eq!(contract.get_stats().num_posts, 3);
+
+        let post: Post = contract.get_post(solution).into();
+        let post = serde_json::to_value(post).unwrap();
+        assert_eq!(post["snapshot"]["description"], "How, v2");
+        assert_eq!(post["revision"], 1);
+        let previous = serde_json::to_value(contract.get_post_revision(solution, 0)).unwrap();
+        assert_eq!(previous["description"], "How");
+        assert_eq!(previous["post_type"], "Solution");
+    }
+
+    #[test]
+    #[should_panic(expected = "Sponsorship cannot be added under this parent")]
+    fn checks_parent_type() {
+        set_deposit("devhub.near", DEPOSIT);
+        let mut contract = Contract::new();
+        let idea = contract.add_post(
+            None,
+            body(json!({"post_type": "Idea", "name": "Idea", "description": "Do it"})),
+            labels(&[]),
+        );
+        contract.add_post(
+            Some(idea),
+            body(json!({
+                "post_type": "Sponsorship", "name": "Sponsorship", "description": "",
+                "amount": "100", "currency": "USDC", "supervisor": "frol.near"
+            })),
+            labels(&[]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account is not allowed to edit this post")]
+    fn only_author_edits() {
+        set_deposit("alice.near", DEPOSIT);
+        let mut contract = Contract::new();
+        let idea = contract.add_post(
+            None,
+            body(json!({"post_type": "Idea", "name": "Idea", "description": "Do it"})),
+            labels(&[]),
+        );
+        set_deposit("bob.near", DEPOSIT);
+        contract.edit_post(
+            idea,
+            body(json!({"post_type": "Idea", "name": "Idea", "description": "Edited"})),
+            labels(&[]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The post cannot be longer than 10000 characters")]
+    fn caps_post_length() {
+        set_deposit("alice.near", DEPOSIT);
+        let mut contract = Contract::new();
+        contract.add_post(
+            None,
+            body(json!({"post_type": "Idea", "name": "Idea", "description": "a".repeat(10000)})),
+            labels(&[]),
+        );
+    }
+}
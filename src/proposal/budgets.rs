@@ -0,0 +1,399 @@
+use near_sdk::json_types::U64;
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::timeline::TimelineStatusKind;
+use super::ProposalId;
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+/// Budget of a category for a period. Amounts are in USD, like the
+/// `requested_sponsorship_usd_amount` of the proposals.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct CategoryBudget {
+    pub period: String,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub start: Timestamp,
+    /// Excluded from the period.
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub end: Timestamp,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub allocated_usd_amount: u64,
+    /// Requested amounts of the approved proposals which are not funded yet.
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub reserved_usd_amount: u64,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub spent_usd_amount: u64,
+}
+
+impl CategoryBudget {
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        self.start <= timestamp && timestamp < self.end
+    }
+
+    fn used_usd_amount(&self) -> u64 {
+        self.reserved_usd_amount + self.spent_usd_amount
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpendingState {
+    Reserved,
+    Spent,
+}
+
+/// Amount of a proposal counted against the budget of its category.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalSpending {
+    pub category: String,
+    pub period: String,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub usd_amount: u64,
+    pub state: SpendingState,
+    /// Moderator who allowed the proposal to go over the budget.
+    pub overridden_by: Option<AccountId>,
+}
+
+#[near(serializers=[json])]
+pub struct CategoryBudgetReport {
+    pub category: String,
+    #[serde(flatten)]
+    pub budget: CategoryBudget,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub remaining_usd_amount: u64,
+}
+
+/// How the requested amount of a proposal in the status is counted against the budget.
+fn spending_state(status: TimelineStatusKind) -> Option<SpendingState> {
+    match status {
+        TimelineStatusKind::Approved
+        | TimelineStatusKind::ApprovedConditionally
+        | TimelineStatusKind::PaymentProcessing => Some(SpendingState::Reserved),
+        TimelineStatusKind::Funded => Some(SpendingState::Spent),
+        _ => None,
+    }
+}
+
+impl Contract {
+    fn get_category_budget_mut(&mut self, category: &str, period: &str) -> &mut CategoryBudget {
+        self.proposal_categories
+            .iter_mut()
+            .find(|other| other.name == category)
+            .and_then(|category| category.budgets.iter_mut().find(|budget| budget.period == period))
+            .unwrap_or_else(|| env::panic_str("Unknown budget period"))
+    }
+
+    fn release_spending(&mut self, spending: &ProposalSpending) {
+        let budget = self.get_category_budget_mut(&spending.category, &spending.period);
+        let (amount, kind) = match spending.state {
+            SpendingState::Reserved => (&mut budget.reserved_usd_amount, "reserved"),
+            SpendingState::Spent => (&mut budget.spent_usd_amount, "spent"),
+        };
+        *amount = amount.checked_sub(spending.usd_amount).unwrap_or_else(|| {
+            env::panic_str(&format!(
+                "The released amount exceeds the {} amount of the {} budget",
+                kind, spending.period
+            ))
+        });
+    }
+
+    /// Counts the requested amount of the proposal against the budget of its category according
+    /// to its new status: approved proposals reserve it and funded ones spend it. A proposal
+    /// keeps the budget period of its approval until it leaves the approved statuses. Proposals
+    /// which were approved without being counted, before the budgets existed or while their
+    /// category had no budget for the period, stay uncounted until they leave these statuses.
+    pub(crate) fn settle_category_budget(
+        &mut self,
+        id: ProposalId,
+        category: &str,
+        requested_usd_amount: u32,
+        previous_status: TimelineStatusKind,
+        status: TimelineStatusKind,
+    ) {
+        let state = spending_state(status);
+        let usd_amount = u64::from(requested_usd_amount);
+        let previous = self.proposal_spending.get(&id);
+        if previous.is_none() && spending_state(previous_status).is_some() {
+            return;
+        }
+        if let Some(previous) = &previous {
+            if Some(previous.state) == state
+                && previous.category == category
+                && previous.usd_amount == usd_amount
+            {
+                return;
+            }
+            self.release_spending(previous);
+        }
+
+        let Some(state) = state else {
+            self.proposal_spending.remove(&id);
+            return;
+        };
+        let period = match previous.as_ref().filter(|previous| previous.category == category) {
+            Some(previous) => Some(previous.period.clone()),
+            None => self.get_category(category).and_then(|category| {
+                let now = env::block_timestamp();
+                let budget = category.budgets.iter().find(|budget| budget.contains(now))?;
+                Some(budget.period.clone())
+            }),
+        };
+        let Some(period) = period else {
+            // The category has no budget for the current period.
+            self.proposal_spending.remove(&id);
+            return;
+        };
+
+        // Moving an approved proposal to funded does not change the amount in use.
+        let already_counted = previous
+            .as_ref()
+            .filter(|previous| previous.category == category && previous.period == period)
+            .map_or(0, |previous| previous.usd_amount);
+        let mut overridden_by = previous.and_then(|previous| previous.overridden_by);
+        let budget = self.get_category_budget_mut(category, &period);
+        if usd_amount > already_counted
+            && budget.used_usd_amount() + usd_amount > budget.allocated_usd_amount
+        {
+            let Some(moderator) = self.budget_overrides.remove(&id) else {
+                env::panic_str(&format!(
+                    "The proposal exceeds the {} budget of the category",
+                    period
+                ));
+            };
+            overridden_by = Some(moderator);
+        }
+
+        let budget = self.get_category_budget_mut(category, &period);
+        match state {
+            SpendingState::Reserved => budget.reserved_usd_amount += usd_amount,
+            SpendingState::Spent => budget.spent_usd_amount += usd_amount,
+        }
+        self.proposal_spending.insert(
+            &id,
+            &ProposalSpending {
+                category: category.to_string(),
+                period,
+                usd_amount,
+                state,
+                overridden_by,
+            },
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Sets the budget of the category for the period `[start, end)`, keeping the amounts already
+    /// reserved and spent in the period.
+    #[payable]
+    pub fn set_category_budget(
+        &mut self,
+        category: String,
+        period: String,
+        start: U64,
+        end: U64,
+        allocated_usd_amount: U64,
+    ) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can set budgets"
+        );
+        require!(start.0 < end.0, "The budget period cannot be empty");
+        let Some(record) = self.proposal_categories.iter_mut().find(|other| other.name == category)
+        else {
+            env::panic_str("Unknown category");
+        };
+        require!(
+            record.budgets.iter().all(|budget| {
+                budget.period == period || budget.end <= start.0 || end.0 <= budget.start
+            }),
+            "The budget period overlaps another period of the category"
+        );
+        match record.budgets.iter_mut().find(|budget| budget.period == period) {
+            Some(budget) => {
+                budget.start = start.0;
+                budget.end = end.0;
+                budget.allocated_usd_amount = allocated_usd_amount.0;
+            }
+            None => record.budgets.push(CategoryBudget {
+                period,
+                start: start.0,
+                end: end.0,
+                allocated_usd_amount: allocated_usd_amount.0,
+                reserved_usd_amount: 0,
+                spent_usd_amount: 0,
+            }),
+        }
+    }
+
+    /// Allows the next approval or funding of the proposal to go over the budget of its category.
+    #[payable]
+    pub fn override_category_budget(&mut self, proposal_id: ProposalId) {
+        let moderator = env::predecessor_account_id();
+        require!(
+            self.has_moderator(moderator.clone()) || moderator == env::current_account_id(),
+            "Only the admin and moderators can override budgets"
+        );
+        self.get_proposal(proposal_id);
+        self.budget_overrides.insert(&proposal_id, &moderator);
+    }
+
+    /// Budgets of the category, or of every category, with their allocated, reserved and spent
+    /// amounts.
+    pub fn get_category_budgets(&self, category: Option<String>) -> Vec<CategoryBudgetReport> {
+        self.proposal_categories
+            .iter()
+            .filter(|record| category.as_ref().map_or(true, |category| &record.name == category))
+            .flat_map(|record| {
+                record.budgets.iter().map(|budget| CategoryBudgetReport {
+                    category: record.name.clone(),
+                    budget: budget.clone(),
+                    remaining_usd_amount: budget
+                        .allocated_usd_amount
+                        .saturating_sub(budget.used_usd_amount()),
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_proposal_spending(&self, proposal_id: ProposalId) -> Option<ProposalSpending> {
+        self.proposal_spending.get(&proposal_id)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::json_types::U64;
+    use near_sdk::serde_json::{self, json};
+
+    use super::SpendingState;
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::Contract;
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        for amount in [3000, 2500] {
+            let body = proposal_body(json!({
+                "category": "Events",
+                "requested_sponsorship_usd_amount": amount.to_string(),
+                "supervisor": "frol.near",
+                "timeline": {
+                    "status": "REVIEW",
+                    "sponsor_requested_review": true,
+                    "reviewer_completed_attestation": false,
+                },
+            }));
+            store_proposal(&mut contract, "bob.near", body);
+        }
+        contract.set_category_budget("Events".into(), "Q1".into(), U64(0), U64(1000), U64(5000));
+        contract
+    }
+
+    fn move_to(contract: &mut Contract, id: u32, status: &str) {
+        contract.edit_proposal_timeline(
+            id,
+            serde_json::from_value(json!({
+                "status": status,
+                "sponsor_requested_review": true,
+                "reviewer_completed_attestation": true,
+                "kyc_verified": true,
+                "test_transaction_sent": true,
+                "request_for_trustees_created": true,
+                "trustees_released_payment": true,
+                "payouts": [],
+            }))
+            .unwrap(),
+        );
+    }
+
+    fn approve(contract: &mut Contract, id: u32) {
+        move_to(contract, id, "APPROVED");
+    }
+
+    #[test]
+    fn reserves_and_spends_budgets() {
+        let mut contract = setup();
+        approve(&mut contract, 0);
+        let budget = &contract.get_category_budgets(Some("Events".into()))[0];
+        assert_eq!(budget.budget.reserved_usd_amount, 3000);
+        assert_eq!(budget.remaining_usd_amount, 2000);
+
+        move_to(&mut contract, 0, "FUNDED");
+        let budget = &contract.get_category_budgets(None)[0];
+        assert_eq!((budget.budget.reserved_usd_amount, budget.budget.spent_usd_amount), (0, 3000));
+        assert_eq!(contract.get_proposal_spending(0).unwrap().state, SpendingState::Spent);
+
+        move_to(&mut contract, 0, "CANCELLED");
+        assert_eq!(contract.get_category_budgets(None)[0].remaining_usd_amount, 5000);
+        assert!(contract.get_proposal_spending(0).is_none());
+    }
+
+    #[test]
+    fn skips_proposals_approved_before_budgets() {
+        let mut contract = setup();
+        approve(&mut contract, 0);
+        contract.proposal_spending.remove(&0);
+        contract.get_category_budget_mut("Events", "Q1").reserved_usd_amount = 0;
+        approve(&mut contract, 1);
+
+        move_to(&mut contract, 0, "FUNDED");
+        assert!(contract.get_proposal_spending(0).is_none());
+        let budget = &contract.get_category_budgets(None)[0];
+        assert_eq!((budget.budget.reserved_usd_amount, budget.budget.spent_usd_amount), (2500, 0));
+    }
+
+    #[test]
+    fn moderators_override_budgets() {
+        let mut contract = setup();
+        approve(&mut contract, 0);
+        contract.override_category_budget(1);
+        approve(&mut contract, 1);
+        let budget = &contract.get_category_budgets(None)[0];
+        assert_eq!(budget.budget.reserved_usd_amount, 5500);
+        assert_eq!(budget.remaining_usd_amount, 0);
+        let spending = contract.get_proposal_spending(1).unwrap();
+        assert_eq!(spending.overridden_by.unwrap().as_str(), "devhub.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal exceeds the Q1 budget of the category")]
+    fn rejects_approvals_over_budget() {
+        let mut contract = setup();
+        approve(&mut contract, 0);
+        approve(&mut contract, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The released amount exceeds the reserved amount of the Q1 budget")]
+    fn refuses_releasing_more_than_reserved() {
+        let mut contract = setup();
+        approve(&mut contract, 0);
+        contract.get_category_budget_mut("Events", "Q1").reserved_usd_amount = 0;
+        move_to(&mut contract, 0, "FUNDED");
+    }
+}
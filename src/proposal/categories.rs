@@ -0,0 +1,217 @@
+use near_sdk::json_types::U64;
+use near_sdk::{env, near, require, AccountId};
+
+use super::budgets::CategoryBudget;
+use super::{ProposalBodyV3, VersionedProposalBody};
+use crate::{Contract, ContractExt};
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalCategory {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Supervisor of the new proposals of the category which do not name one.
+    #[serde(default)]
+    pub default_supervisor: Option<AccountId>,
+    /// Upper bound of the `requested_sponsorship_usd_amount` of a single proposal.
+    #[serde(default)]
+    pub budget_cap: Option<U64>,
+    /// Inactive categories are refused on new proposals but kept by the existing ones.
+    pub active: bool,
+    /// Set by `set_category_budget`.
+    #[serde(default)]
+    pub budgets: Vec<CategoryBudget>,
+}
+
+impl ProposalCategory {
+    pub fn new(name: String) -> Self {
+        ProposalCategory {
+            name,
+            description: String::new(),
+            default_supervisor: None,
+            budget_cap: None,
+            active: true,
+            budgets: vec![],
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn get_category(&self, name: &str) -> Option<&ProposalCategory> {
+        self.proposal_categories.iter().find(|category| category.name == name)
+    }
+
+    pub(crate) fn assert_known_category(&self, name: &str) {
+        require!(self.get_category(name).is_some(), "Unknown category");
+    }
+
+    /// Checks the category of the proposal body against the category record. `previous` is the
+    /// body before the edit, `None` for a new proposal. Proposals which do not change their
+    /// category or their requested amount keep them even if the category changed since.
+    pub(crate) fn assert_category_allows(
+        &self,
+        body: &ProposalBodyV3,
+        previous: Option<&ProposalBodyV3>,
+    ) {
+        let Some(category) = self.get_category(&body.category) else {
+            env::panic_str("Unknown category");
+        };
+        let category_changed = previous.map_or(true, |previous| previous.category != body.category);
+        require!(category.active || !category_changed, "The category is not active");
+        let amount_changed = previous.map_or(true, |previous| {
+            previous.requested_sponsorship_usd_amount != body.requested_sponsorship_usd_amount
+        });
+        if let Some(budget_cap) = category.budget_cap {
+            require!(
+                !(category_changed || amount_changed)
+                    || u64::from(body.requested_sponsorship_usd_amount) <= budget_cap.0,
+                "The requested amount exceeds the budget cap of the category"
+            );
+        }
+    }
+
+    /// Fills in the default supervisor of the category when the body does not name one.
+    pub(crate) fn apply_category_defaults(
+        &self,
+        body: VersionedProposalBody,
+    ) -> VersionedProposalBody {
+        let mut latest = body.clone().latest_version();
+        let default_supervisor = self
+            .get_category(&latest.category)
+            .and_then(|category| category.default_supervisor.clone());
+        match (&latest.supervisor, default_supervisor) {
+            (None, Some(supervisor)) => {
+                latest.supervisor = Some(supervisor);
+                latest.into()
+            }
+            _ => body,
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    pub fn get_categories(&self) -> Vec<ProposalCategory> {
+        self.proposal_categories.clone()
+    }
+
+    /// Adds the category or replaces the record of the category with the same name. The budgets
+    /// are only set by `set_category_budget`, so the ones of the argument are ignored.
+    #[payable]
+    pub fn set_category(&mut self, category: ProposalCategory) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can set categories"
+        );
+        require!(!category.name.trim().is_empty(), "The category name cannot be empty");
+        match self.proposal_categories.iter_mut().find(|other| other.name == category.name) {
+            Some(other) => {
+                *other =
+                    ProposalCategory { budgets: std::mem::take(&mut other.budgets), ..category }
+            }
+            None => self
+                .proposal_categories
+                .push(ProposalCategory { budgets: Default::default(), ..category }),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::json_types::U64;
+    use near_sdk::serde_json::json;
+
+    use super::ProposalCategory;
+    use crate::proposal::budgets::CategoryBudget;
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::{Contract, Proposal, VersionedProposalBody};
+
+    fn body(category: &str, amount: u32) -> VersionedProposalBody {
+        proposal_body(json!({
+            "category": category,
+            "requested_sponsorship_usd_amount": amount.to_string(),
+        }))
+    }
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        contract.set_category(ProposalCategory {
+            name: "Events".into(),
+            description: "Meetups and hackathons".into(),
+            default_supervisor: Some("frol.near".parse().unwrap()),
+            budget_cap: Some(U64(5000)),
+            active: true,
+            budgets: vec![],
+        });
+        contract
+    }
+
+    #[test]
+    fn applies_category_records() {
+        let mut contract = setup();
+        let categories = contract.get_categories();
+        assert_eq!(categories.len(), 7);
+        assert_eq!(categories[4].description, "Meetups and hackathons");
+
+        let body = contract.apply_category_defaults(body("Events", 1000));
+        assert_eq!(body.latest_version().supervisor.unwrap().as_str(), "frol.near");
+        let body = contract.apply_category_defaults(self::body("Marketing", 1000));
+        assert!(body.latest_version().supervisor.is_none());
+
+        contract.set_allowed_categories(vec!["Marketing".into(), "Research".into()]);
+        assert_eq!(contract.get_allowed_categories(), vec!["Marketing", "Research"]);
+        assert_eq!(contract.get_categories().len(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "The requested amount exceeds the budget cap of the category")]
+    fn enforces_budget_cap() {
+        let mut contract = setup();
+        set_predecessor("bob.near");
+        contract.add_proposal(body("Events", 6000), HashSet::new(), Some(0));
+    }
+
+    #[test]
+    fn keeps_inactive_categories_on_existing_proposals() {
+        let mut contract = setup();
+        store_proposal(&mut contract, "bob.near", body("Events", 1000));
+        contract.set_allowed_categories(vec!["Marketing".into()]);
+
+        set_predecessor("bob.near");
+        contract.edit_proposal(0, body("Events", 1000), HashSet::new());
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.revision, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The category is not active")]
+    fn refuses_inactive_categories_on_new_proposals() {
+        let mut contract = setup();
+        contract.set_allowed_categories(vec!["Marketing".into()]);
+        set_predecessor("bob.near");
+        contract.add_proposal(body("Events", 1000), HashSet::new(), Some(0));
+    }
+
+    #[test]
+    fn ignores_budgets_of_new_categories() {
+        let mut contract = setup();
+        contract.set_category(ProposalCategory {
+            budgets: vec![CategoryBudget {
+                period: "2024".into(),
+                start: 0,
+                end: 1,
+                allocated_usd_amount: 1000,
+                reserved_usd_amount: 0,
+                spent_usd_amount: 0,
+            }],
+            ..ProposalCategory::new("Tooling".into())
+        });
+        let category = contract.get_category("Tooling").unwrap();
+        assert!(category.budgets.is_empty());
+    }
+}
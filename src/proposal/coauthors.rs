@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use near_sdk::{env, near, require, AccountId};
+
+use super::{Proposal, ProposalId};
+use crate::{Contract, ContractExt};
+
+impl Contract {
+    pub(crate) fn is_proposal_coauthor(&self, id: ProposalId, account_id: &AccountId) -> bool {
+        self.proposal_coauthors.get(&id).is_some_and(|coauthors| coauthors.contains(account_id))
+    }
+
+    /// The author followed by the co-authors of the proposal.
+    pub(crate) fn get_proposal_authors(&self, proposal: &Proposal) -> Vec<AccountId> {
+        let mut coauthors: Vec<AccountId> =
+            self.proposal_coauthors.get(&proposal.id).unwrap_or_default().into_iter().collect();
+        coauthors.sort();
+        [vec![proposal.author_id.clone()], coauthors].concat()
+    }
+
+    fn assert_proposal_author(&self, proposal: &Proposal) {
+        require!(
+            env::predecessor_account_id() == proposal.author_id,
+            "Only the author can manage the co-authors of the proposal"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Allows the account to edit the proposal, except its receiver account.
+    pub fn add_proposal_coauthor(&mut self, proposal_id: ProposalId, account_id: AccountId) {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.assert_proposal_author(&proposal);
+        require!(account_id != proposal.author_id, "The author cannot be a co-author");
+
+        let mut coauthors = self.proposal_coauthors.get(&proposal_id).unwrap_or_default();
+        require!(coauthors.insert(account_id.clone()), "The account is already a co-author");
+        self.proposal_coauthors.insert(&proposal_id, &coauthors);
+
+        let mut proposals = self.coauthor_proposals.get(&account_id).unwrap_or_default();
+        proposals.insert(proposal_id);
+        self.coauthor_proposals.insert(&account_id, &proposals);
+    }
+
+    pub fn remove_proposal_coauthor(&mut self, proposal_id: ProposalId, account_id: AccountId) {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.assert_proposal_author(&proposal);
+
+        let mut coauthors = self.proposal_coauthors.get(&proposal_id).unwrap_or_default();
+        require!(coauthors.remove(&account_id), "The account is not a co-author");
+        self.proposal_coauthors.insert(&proposal_id, &coauthors);
+
+        let mut proposals = self.coauthor_proposals.get(&account_id).unwrap_or_default();
+        proposals.remove(&proposal_id);
+        self.coauthor_proposals.insert(&account_id, &proposals);
+    }
+
+    pub fn get_proposal_coauthors(&self, proposal_id: ProposalId) -> HashSet<AccountId> {
+        self.proposal_coauthors.get(&proposal_id).unwrap_or_default()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::get_created_receipts;
+
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::{Contract, Proposal};
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(&mut contract, "bob.near", proposal_body(json!({})));
+
+        set_predecessor("bob.near");
+        contract.add_proposal_coauthor(0, "alice.near".parse().unwrap());
+        contract
+    }
+
+    #[test]
+    fn coauthors_edit_and_get_notified() {
+        let mut contract = setup();
+        assert_eq!(contract.get_proposals_by_author("alice.near".parse().unwrap()), vec![0]);
+
+        set_predecessor("alice.near");
+        contract.edit_proposal(
+            0,
+            proposal_body(json!({"summary": "Updated summary"})),
+            HashSet::new(),
+        );
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.snapshot.editor_id.as_str(), "alice.near");
+
+        let receipts = get_created_receipts();
+        let near_sdk::mock::MockAction::FunctionCallWeight { args, .. } = &receipts[0].actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        let args = String::from_utf8(args.clone()).unwrap();
+        assert!(args.contains("proposal/edit"));
+        assert!(args.contains("bob.near") && args.contains("alice.near"));
+
+        set_predecessor("bob.near");
+        contract.remove_proposal_coauthor(0, "alice.near".parse().unwrap());
+        assert!(contract.get_proposal_coauthors(0).is_empty());
+        assert!(contract.get_proposals_by_author("alice.near".parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Co-authors cannot change the receiver account")]
+    fn coauthors_keep_receiver_account() {
+        let mut contract = setup();
+        set_predecessor("alice.near");
+        contract.edit_proposal(
+            0,
+            proposal_body(json!({"receiver_account": "alice.near"})),
+            HashSet::new(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the author can manage the co-authors of the proposal")]
+    fn only_author_manages_coauthors() {
+        let mut contract = setup();
+        set_predecessor("alice.near");
+        contract.add_proposal_coauthor(0, "carol.near".parse().unwrap());
+    }
+}
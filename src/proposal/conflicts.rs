@@ -0,0 +1,210 @@
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::{Proposal, ProposalBodyV3, ProposalId};
+use crate::common::charge_storage_deposit;
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+/// Maximum number of characters of the reason of a conflict declaration.
+pub const MAX_CONFLICT_REASON_LENGTH: usize = 1000;
+
+/// Conflict of interest declared by an account which recuses itself from a proposal.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ConflictDeclaration {
+    pub account_id: AccountId,
+    pub reason: String,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+}
+
+fn is_party(body: &ProposalBodyV3, account_id: &AccountId) -> bool {
+    &body.requested_sponsor == account_id
+        || &body.receiver_account == account_id
+        || body.supervisor.as_ref() == Some(account_id)
+}
+
+impl Contract {
+    pub(crate) fn has_declared_conflict(&self, id: ProposalId, account_id: &AccountId) -> bool {
+        self.account_conflicts.get(account_id).is_some_and(|proposals| proposals.contains(&id))
+    }
+
+    /// Whether the account is an author, a co-author or a party of the proposal and so cannot act
+    /// as its moderator or reviewer.
+    pub(crate) fn is_self_interested(&self, proposal: &Proposal, account_id: &AccountId) -> bool {
+        &proposal.author_id == account_id
+            || self.is_proposal_coauthor(proposal.id, account_id)
+            || is_party(&proposal.snapshot.body.clone().latest_version(), account_id)
+    }
+
+    pub(crate) fn has_conflict(&self, proposal: &Proposal, account_id: &AccountId) -> bool {
+        self.has_declared_conflict(proposal.id, account_id)
+            || self.is_self_interested(proposal, account_id)
+    }
+}
+
+#[near]
+impl Contract {
+    /// Recuses the caller from reviewing the proposal and from moving it along its timeline. The
+    /// attached deposit has to cover the storage of the declaration, the rest is refunded.
+    #[payable]
+    pub fn declare_conflict(&mut self, proposal_id: ProposalId, reason: String) -> ProposalId {
+        self.get_proposal(proposal_id);
+        let account_id = env::predecessor_account_id();
+        require!(!reason.trim().is_empty(), "The reason cannot be empty");
+        require!(
+            reason.chars().count() <= MAX_CONFLICT_REASON_LENGTH,
+            format!("The reason cannot be longer than {} characters", MAX_CONFLICT_REASON_LENGTH)
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let mut proposals = self.account_conflicts.get(&account_id).unwrap_or_default();
+        require!(proposals.insert(proposal_id), "The conflict is already declared");
+        self.account_conflicts.insert(&account_id, &proposals);
+
+        let mut declarations = self.proposal_conflicts.get(&proposal_id).unwrap_or_default();
+        declarations.push(ConflictDeclaration {
+            account_id,
+            reason,
+            timestamp: env::block_timestamp(),
+        });
+        self.proposal_conflicts.insert(&proposal_id, &declarations);
+        charge_storage_deposit(initial_storage_usage);
+        proposal_id
+    }
+
+    /// Withdraws the conflict the caller declared on the proposal, so that the caller can review
+    /// and moderate it again.
+    pub fn withdraw_conflict(&mut self, proposal_id: ProposalId) -> ProposalId {
+        let account_id = env::predecessor_account_id();
+        let mut proposals = self.account_conflicts.get(&account_id).unwrap_or_default();
+        require!(proposals.remove(&proposal_id), "There is no conflict declared on this proposal");
+        if proposals.is_empty() {
+            self.account_conflicts.remove(&account_id);
+        } else {
+            self.account_conflicts.insert(&account_id, &proposals);
+        }
+
+        let mut declarations = self.proposal_conflicts.get(&proposal_id).unwrap_or_default();
+        declarations.retain(|declaration| declaration.account_id != account_id);
+        if declarations.is_empty() {
+            self.proposal_conflicts.remove(&proposal_id);
+        } else {
+            self.proposal_conflicts.insert(&proposal_id, &declarations);
+        }
+        proposal_id
+    }
+
+    pub fn get_proposal_conflicts(&self, proposal_id: ProposalId) -> Vec<ConflictDeclaration> {
+        self.proposal_conflicts.get(&proposal_id).unwrap_or_default()
+    }
+
+    pub fn get_account_conflicts(&self, account_id: AccountId) -> Vec<ProposalId> {
+        let mut proposals: Vec<ProposalId> =
+            self.account_conflicts.get(&account_id).unwrap_or_default().into_iter().collect();
+        proposals.sort();
+        proposals
+    }
+
+    /// Whether the account declared a conflict on the proposal or is one of its authors or
+    /// parties.
+    pub fn has_conflict_of_interest(&self, proposal_id: ProposalId, account_id: AccountId) -> bool {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.has_conflict(&proposal, &account_id)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::NearToken;
+
+    use crate::access_control::members::{Member, MemberMetadata};
+    use crate::proposal::timeline::TimelineStatusKind;
+    use crate::test_fixtures::{proposal_body, set_deposit, set_predecessor, store_proposal};
+    use crate::Contract;
+
+    const DEPOSIT: NearToken = NearToken::from_millinear(10);
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        let body = proposal_body(json!({
+            "supervisor": "frol.near",
+            "timeline": {"status": "REVIEW", "sponsor_requested_review": true, "reviewer_completed_attestation": false},
+        }));
+        store_proposal(&mut contract, "bob.near", body);
+
+        let moderators = [
+            Member::Account("alice.near".parse().unwrap()),
+            Member::Account("frol.near".parse().unwrap()),
+        ];
+        let members_list = &mut contract.access_control.members_list;
+        for moderator in &moderators {
+            members_list.add_member(moderator.clone(), MemberMetadata::default().into());
+        }
+        members_list.add_member(
+            Member::Team("moderators".to_string()),
+            MemberMetadata { children: moderators.into(), ..Default::default() }.into(),
+        );
+        contract
+    }
+
+    #[test]
+    fn recuses_conflicted_moderators() {
+        let mut contract = setup();
+        assert!(contract
+            .get_allowed_transitions(0, "alice.near".parse().unwrap())
+            .contains(&TimelineStatusKind::Approved));
+        // The supervisor keeps its own role but loses the one of a moderator.
+        assert!(!contract
+            .get_allowed_transitions(0, "frol.near".parse().unwrap())
+            .contains(&TimelineStatusKind::Approved));
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.declare_conflict(0, "I advise the team".to_string());
+        assert!(contract.get_allowed_transitions(0, "alice.near".parse().unwrap()).is_empty());
+        assert_eq!(contract.get_account_conflicts("alice.near".parse().unwrap()), vec![0]);
+        assert_eq!(contract.get_proposal_conflicts(0)[0].reason, "I advise the team");
+        assert!(contract.has_conflict_of_interest(0, "neardevdao.near".parse().unwrap()));
+
+        contract.withdraw_conflict(0);
+        assert!(contract
+            .get_allowed_transitions(0, "alice.near".parse().unwrap())
+            .contains(&TimelineStatusKind::Approved));
+        assert!(contract.get_account_conflicts("alice.near".parse().unwrap()).is_empty());
+        assert!(contract.get_proposal_conflicts(0).is_empty());
+    }
+
+    #[test]
+    fn recuses_authors_and_coauthors() {
+        let mut contract = setup();
+        set_predecessor("bob.near");
+        contract.add_proposal_coauthor(0, "alice.near".parse().unwrap());
+
+        assert!(contract.has_conflict_of_interest(0, "bob.near".parse().unwrap()));
+        assert!(contract.has_conflict_of_interest(0, "alice.near".parse().unwrap()));
+        // The co-author keeps the role of an author but loses the one of a moderator.
+        assert!(!contract
+            .get_allowed_transitions(0, "alice.near".parse().unwrap())
+            .contains(&TimelineStatusKind::Approved));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account is not allowed to edit this proposal")]
+    fn blocks_timeline_changes_after_declaration() {
+        let mut contract = setup();
+        set_deposit("alice.near", DEPOSIT);
+        contract.declare_conflict(0, "I advise the team".to_string());
+        contract.edit_proposal_timeline(
+            0,
+            serde_json::from_value(json!({
+                "status": "APPROVED", "sponsor_requested_review": true, "reviewer_completed_attestation": true
+            }))
+            .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use near_sdk::near;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json::{self, Value};
+
+use super::timeline::TimelineStatus;
+use super::{ProposalBodyV3, ProposalId, ProposalRevision, ProposalSnapshot};
+use crate::str_serializers::*;
+
+/// Upper bound on the size of the table of the longest common subsequence, in pairs of changed
+/// lines. Larger changes are reported as the deletion of every changed line followed by the
+/// insertion of the new ones.
+pub const MAX_DIFF_CELLS: usize = 40_000;
+
+/// Structured difference between two revisions of a proposal. Fields which did not change are
+/// left out: empty lists and `None`.
+#[near(serializers=[json])]
+pub struct ProposalDiff {
+    pub proposal_id: ProposalId,
+    pub from_revision: ProposalRevision,
+    pub to_revision: ProposalRevision,
+    /// Changes of the fields holding a single value, e.g. `name` or `supervisor`.
+    pub changed_fields: Vec<FieldChange>,
+    pub requested_sponsorship_usd_amount: Option<AmountChange>,
+    pub linked_proposals: SetChange<ProposalId>,
+    pub labels: SetChange<String>,
+    pub timeline: Option<TimelineChange>,
+    pub description: Option<Vec<LineChange>>,
+    pub summary: Option<Vec<LineChange>>,
+}
+
+#[near(serializers=[json])]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[near(serializers=[json])]
+pub struct AmountChange {
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub from: u32,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub to: u32,
+}
+
+#[near(serializers=[json])]
+pub struct SetChange<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+}
+
+#[near(serializers=[json])]
+pub struct TimelineChange {
+    pub from: TimelineStatus,
+    pub to: TimelineStatus,
+}
+
+#[near(serializers=[json])]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum LineChange {
+    Equal { line: String },
+    Insert { line: String },
+    Delete { line: String },
+}
+
+pub fn diff_proposal_snapshots(
+    proposal_id: ProposalId,
+    from_revision: ProposalRevision,
+    from: ProposalSnapshot,
+    to_revision: ProposalRevision,
+    to: ProposalSnapshot,
+) -> ProposalDiff {
+    let from_body = from.body.latest_version();
+    let to_body = to.body.latest_version();
+
+    let requested_sponsorship_usd_amount = (from_body.requested_sponsorship_usd_amount
+        != to_body.requested_sponsorship_usd_amount)
+        .then_some(AmountChange {
+            from: from_body.requested_sponsorship_usd_amount,
+            to: to_body.requested_sponsorship_usd_amount,
+        });
+    let from_timeline = from_body.timeline.clone().latest_version();
+    let to_timeline = to_body.timeline.clone().latest_version();
+    let timeline = (serde_json::to_value(&from_timeline).unwrap()
+        != serde_json::to_value(&to_timeline).unwrap())
+    .then_some(TimelineChange { from: from_timeline, to: to_timeline });
+
+    ProposalDiff {
+        proposal_id,
+        from_revision,
+        to_revision,
+        changed_fields: changed_fields(&from_body, &to_body),
+        requested_sponsorship_usd_amount,
+        linked_proposals: set_change(
+            from_body.linked_proposals.iter().copied().collect(),
+            to_body.linked_proposals.iter().copied().collect(),
+        ),
+        labels: set_change(from.labels, to.labels),
+        timeline,
+        description: diff_text(&from_body.description, &to_body.description),
+        summary: diff_text(&from_body.summary, &to_body.summary),
+    }
+}
+
+fn changed_fields(from: &ProposalBodyV3, to: &ProposalBodyV3) -> Vec<FieldChange> {
+    let mut changes = vec![];
+    let mut compare = |field: &str, from: Value, to: Value| {
+        if from != to {
+            changes.push(FieldChange { field: field.to_string(), from, to });
+        }
+    };
+    compare("name", value(&from.name), value(&to.name));
+    compare("category", value(&from.category), value(&to.category));
+    compare(
+        "requested_sponsorship_paid_in_currency",
+        value(&from.requested_sponsorship_paid_in_currency),
+        value(&to.requested_sponsorship_paid_in_currency),
+    );
+    compare("receiver_account", value(&from.receiver_account), value(&to.receiver_account));
+    compare("requested_sponsor", value(&from.requested_sponsor), value(&to.requested_sponsor));
+    compare("supervisor", value(&from.supervisor), value(&to.supervisor));
+    compare("linked_rfp", value(&from.linked_rfp), value(&to.linked_rfp));
+    compare("milestones", value(&from.milestones), value(&to.milestones));
+    changes
+}
+
+fn value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+fn set_change<T: Ord + Clone + std::hash::Hash>(from: HashSet<T>, to: HashSet<T>) -> SetChange<T> {
+    let mut added: Vec<T> = to.difference(&from).cloned().collect();
+    let mut removed: Vec<T> = from.difference(&to).cloned().collect();
+    added.sort();
+    removed.sort();
+    SetChange { added, removed }
+}
+
+/// Line-based diff of two texts, `None` when they are equal. The common prefix and suffix are
+/// skipped before computing the longest common subsequence of the remaining lines, up to
+/// `MAX_DIFF_CELLS`.
+pub fn diff_text(from: &str, to: &str) -> Option<Vec<LineChange>> {
+    if from == to {
+        return None;
+    }
+    let from: Vec<&str> = from.lines().collect();
+    let to: Vec<&str> = to.lines().collect();
+
+    let prefix = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let suffix = from[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&from[prefix..from.len() - suffix], &to[prefix..to.len() - suffix]);
+    let line = |line: &str| line.to_string();
+    let mut changes: Vec<LineChange> =
+        from[..prefix].iter().map(|l| LineChange::Equal { line: line(l) }).collect();
+
+    if (old.len() + 1).saturating_mul(new.len() + 1) > MAX_DIFF_CELLS {
+        changes.extend(old.iter().map(|l| LineChange::Delete { line: line(l) }));
+        changes.extend(new.iter().map(|l| LineChange::Insert { line: line(l) }));
+        changes.extend(
+            from[from.len() - suffix..].iter().map(|l| LineChange::Equal { line: line(l) }),
+        );
+        return Some(changes);
+    }
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lcs = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(LineChange::Equal { line: line(old[i]) });
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(LineChange::Delete { line: line(old[i]) });
+            i += 1;
+        } else {
+            changes.push(LineChange::Insert { line: line(new[j]) });
+            j += 1;
+        }
+    }
+    changes.extend(from[from.len() - suffix..].iter().map(|l| LineChange::Equal { line: line(l) }));
+    Some(changes)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+
+    use super::{diff_proposal_snapshots, diff_text};
+    use crate::ProposalSnapshot;
+
+    fn snapshot(body: serde_json::Value) -> ProposalSnapshot {
+        let mut snapshot = json!({
+            "editor_id": "bob.near",
+            "timestamp": "0",
+            "proposal_body_version": "V0",
+            "name": "proposal",
+            "category": "Marketing",
+            "summary": "sum",
+            "description": "description",
+            "requested_sponsorship_paid_in_currency": "USDT",
+            "receiver_account": "bob.near",
+            "requested_sponsor": "neardevdao.near",
+            "supervisor": null,
+        });
+        snapshot.as_object_mut().unwrap().extend(body.as_object().unwrap().clone());
+        serde_json::from_value(snapshot).unwrap()
+    }
+
+    #[test]
+    fn diffs_lines() {
+        assert!(diff_text("a\nb", "a\nb").is_none());
+
+        let changes = diff_text("a\nb\nc\nd", "a\nx\nc\nd\ne").unwrap();
+        assert_eq!(
+            serde_json::to_value(changes).unwrap(),
+            json!([
+                {"op": "equal", "line": "a"},
+                {"op": "delete", "line": "b"},
+                {"op": "insert", "line": "x"},
+                {"op": "equal", "line": "c"},
+                {"op": "equal", "line": "d"},
+                {"op": "insert", "line": "e"},
+            ])
+        );
+    }
+
+    #[test]
+    fn replaces_large_changes() {
+        let from: Vec<String> = (0..300).map(|i| format!("old {}", i)).collect();
+        let to: Vec<String> = (0..300).map(|i| format!("new {}", i)).collect();
+        let from = format!("title\n{}\nend", from.join("\n"));
+        let to = format!("title\n{}\nend", to.join("\n"));
+        let changes = serde_json::to_value(diff_text(&from, &to).unwrap()).unwrap();
+        let changes = changes.as_array().unwrap();
+        assert_eq!(changes.len(), 602);
+        assert_eq!(changes[0], json!({"op": "equal", "line": "title"}));
+        assert_eq!(changes[1], json!({"op": "delete", "line": "old 0"}));
+        assert_eq!(changes[301], json!({"op": "insert", "line": "new 0"}));
+        assert_eq!(changes[601], json!({"op": "equal", "line": "end"}));
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        let from = snapshot(json!({
+            "labels": ["a", "b"],
+            "linked_proposals": [1, 2],
+            "requested_sponsorship_usd_amount": "1000",
+            "timeline": {"status": "DRAFT"},
+        }));
+        let to = snapshot(json!({
+            "labels": ["b", "c"],
+            "linked_proposals": [2, 3],
+            "requested_sponsorship_usd_amount": "1500",
+            "supervisor": "frol.near",
+            "timeline": {"status": "REVIEW", "sponsor_requested_review": false, "reviewer_completed_attestation": false},
+        }));
+        let diff = serde_json::to_value(diff_proposal_snapshots(0, 0, from, 1, to)).unwrap();
+        assert_eq!(
+            diff["changed_fields"],
+            json!([{"field": "supervisor", "from": null, "to": "frol.near"}])
+        );
+        assert_eq!(diff["requested_sponsorship_usd_amount"], json!({"from": "1000", "to": "1500"}));
+        assert_eq!(diff["linked_proposals"], json!({"added": [3], "removed": [1]}));
+        assert_eq!(diff["labels"], json!({"added": ["c"], "removed": ["a"]}));
+        assert_eq!(diff["timeline"]["from"]["status"], "DRAFT");
+        assert_eq!(diff["timeline"]["to"]["status"], "REVIEW");
+        assert_eq!(diff["description"], json!(null));
+    }
+}
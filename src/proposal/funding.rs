@@ -0,0 +1,376 @@
+use std::collections::{BTreeMap, HashSet};
+
+use near_sdk::{near, require};
+
+use super::query::MAX_PAGE_SCAN;
+use super::timeline::Payout;
+use super::{Proposal, ProposalBodyV3, ProposalFundingCurrency, ProposalId};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+/// Amount in whole units of `currency`.
+#[near(serializers=[json])]
+pub struct CurrencyAmount {
+    pub currency: ProposalFundingCurrency,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub amount: u64,
+}
+
+#[near(serializers=[json])]
+pub struct ProposalFunding {
+    pub proposal_id: ProposalId,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub requested_sponsorship_usd_amount: u32,
+    pub requested_sponsorship_paid_in_currency: ProposalFundingCurrency,
+    pub paid: Vec<CurrencyAmount>,
+}
+
+/// Totals of the approved proposals of a category. The requested amount is in USD, the paid
+/// amounts are in the units of the currency of the payouts.
+#[near(serializers=[json])]
+pub struct CategoryFunding {
+    pub category: String,
+    pub approved_proposals: u32,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub requested_usd_amount: u64,
+    pub paid: Vec<CurrencyAmount>,
+}
+
+/// Totals of the approved proposals for a currency. `requested_usd_amount` is the amount in USD
+/// requested to be paid in the currency, `paid_amount` is the amount paid in units of the
+/// currency.
+#[near(serializers=[json])]
+pub struct CurrencyFunding {
+    pub currency: ProposalFundingCurrency,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub requested_usd_amount: u64,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub paid_amount: u64,
+}
+
+/// Totals of the proposals from the requested cursor up to `next_cursor`. The totals of all the
+/// proposals are the sums over the pages.
+#[near(serializers=[json])]
+pub struct CategoryFundingPage {
+    pub categories: Vec<CategoryFunding>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more proposals.
+    pub next_cursor: Option<ProposalId>,
+}
+
+/// Totals of the proposals from the requested cursor up to `next_cursor`, see
+/// `CategoryFundingPage`.
+#[near(serializers=[json])]
+pub struct CurrencyFundingPage {
+    pub currencies: Vec<CurrencyFunding>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more proposals.
+    pub next_cursor: Option<ProposalId>,
+}
+
+#[derive(Default)]
+struct Totals {
+    approved_proposals: u32,
+    requested_usd: BTreeMap<ProposalFundingCurrency, u64>,
+    paid: BTreeMap<ProposalFundingCurrency, u64>,
+}
+
+impl Totals {
+    fn add(&mut self, proposal: &Proposal) {
+        let body = proposal.snapshot.body.clone().latest_version();
+        let timeline = body.timeline.clone().latest_version();
+        if timeline.was_approved() {
+            self.approved_proposals += 1;
+            *self.requested_usd.entry(body.requested_sponsorship_paid_in_currency).or_default() +=
+                u64::from(body.requested_sponsorship_usd_amount);
+        }
+        for payout in payouts(&body) {
+            *self.paid.entry(payout.currency).or_default() += u64::from(payout.amount);
+        }
+    }
+}
+
+/// Payouts of the timeline and of the milestones of the proposal.
+fn payouts(body: &ProposalBodyV3) -> Vec<Payout> {
+    let timeline = body.timeline.clone().latest_version();
+    let milestone_payouts = body.milestones.iter().filter_map(|milestone| milestone.payout.clone());
+    timeline.get_payouts().iter().cloned().chain(milestone_payouts).collect()
+}
+
+fn to_amounts(totals: BTreeMap<ProposalFundingCurrency, u64>) -> Vec<CurrencyAmount> {
+    totals.into_iter().map(|(currency, amount)| CurrencyAmount { currency, amount }).collect()
+}
+
+impl Contract {
+    /// Checks a payout to record for the proposal: a positive amount paid to the receiver account
+    /// of the proposal with a transaction which is not recorded yet.
+    pub(crate) fn assert_valid_payout(&self, body: &ProposalBodyV3, payout: &Payout) {
+        require!(payout.amount > 0, "The payout amount must be positive");
+        require!(
+            payout.receiver == body.receiver_account,
+            "The payout receiver must be the receiver account of the proposal"
+        );
+        require!(
+            !payout.transaction_hash.trim().is_empty(),
+            "The payout transaction hash cannot be empty"
+        );
+        require!(
+            !self.payout_transactions.contains_key(&payout.transaction_hash),
+            format!("The payout transaction {} is already recorded", payout.transaction_hash)
+        );
+    }
+
+    /// Checks the payouts added to the proposal by an edit and records their transactions. The
+    /// transactions of the removed payouts are released.
+    pub(crate) fn settle_payouts(
+        &mut self,
+        id: ProposalId,
+        body: &ProposalBodyV3,
+        previous: &ProposalBodyV3,
+    ) {
+        let previous: HashSet<String> =
+            payouts(previous).into_iter().map(|payout| payout.transaction_hash).collect();
+        let mut current = HashSet::new();
+        for payout in payouts(body) {
+            if !previous.contains(&payout.transaction_hash) {
+                self.assert_valid_payout(body, &payout);
+                self.payout_transactions.insert(&payout.transaction_hash, &id);
+            }
+            require!(
+                current.insert(payout.transaction_hash.clone()),
+                format!("The payout transaction {} is already recorded", payout.transaction_hash)
+            );
+        }
+        for transaction_hash in previous.difference(&current) {
+            self.payout_transactions.remove(transaction_hash);
+        }
+    }
+
+    /// Totals of up to `limit` proposals from `cursor`, grouped by `key`.
+    fn funding_totals<K: Ord>(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+        key: impl Fn(&Proposal) -> K,
+    ) -> (BTreeMap<K, Totals>, Option<ProposalId>) {
+        let limit = limit.unwrap_or(MAX_PAGE_SCAN).min(MAX_PAGE_SCAN);
+        let cursor = cursor.unwrap_or(0);
+        let total: ProposalId = self.proposals.len().try_into().unwrap();
+        let end = total.min(cursor.saturating_add(limit));
+        let mut totals: BTreeMap<K, Totals> = BTreeMap::new();
+        for id in cursor..end {
+            let proposal: Proposal = self.proposals.get(id.into()).unwrap().into();
+            totals.entry(key(&proposal)).or_default().add(&proposal);
+        }
+        (totals, (end < total).then_some(end))
+    }
+}
+
+#[near]
+impl Contract {
+    pub fn get_proposal_funding(&self, proposal_id: ProposalId) -> ProposalFunding {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        let mut totals = Totals::default();
+        totals.add(&proposal);
+        let body = proposal.snapshot.body.latest_version();
+        ProposalFunding {
+            proposal_id,
+            requested_sponsorship_usd_amount: body.requested_sponsorship_usd_amount,
+            requested_sponsorship_paid_in_currency: body.requested_sponsorship_paid_in_currency,
+            paid: to_amounts(totals.paid),
+        }
+    }
+
+    /// Funding totals per category of up to `limit` proposals starting from `cursor`.
+    pub fn get_funding_by_category(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+    ) -> CategoryFundingPage {
+        let (categories, next_cursor) = self.funding_totals(cursor, limit, |proposal| {
+            proposal.snapshot.body.clone().latest_version().category
+        });
+        let categories = categories
+            .into_iter()
+            .map(|(category, totals)| CategoryFunding {
+                category,
+                approved_proposals: totals.approved_proposals,
+                requested_usd_amount: totals.requested_usd.values().sum(),
+                paid: to_amounts(totals.paid),
+            })
+            .collect();
+        CategoryFundingPage { categories, next_cursor }
+    }
+
+    /// Funding totals per currency of up to `limit` proposals starting from `cursor`.
+    pub fn get_funding_by_currency(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+    ) -> CurrencyFundingPage {
+        let (totals, next_cursor) = self.funding_totals(cursor, limit, |_| ());
+        let totals = totals.into_values().next().unwrap_or_default();
+        let mut currencies: Vec<_> =
+            totals.requested_usd.keys().chain(totals.paid.keys()).collect();
+        currencies.sort();
+        currencies.dedup();
+        let currencies = currencies
+            .into_iter()
+            .map(|currency| CurrencyFunding {
+                currency: *currency,
+                requested_usd_amount: totals
+                    .requested_usd
+                    .get(currency)
+                    .copied()
+                    .unwrap_or_default(),
+                paid_amount: totals.paid.get(currency).copied().unwrap_or_default(),
+            })
+            .collect();
+        CurrencyFundingPage { currencies, next_cursor }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    use crate::test_fixtures::{proposal_body, store_proposal};
+    use crate::{Contract, Proposal};
+
+    #[test]
+    fn totals_paid_and_requested() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut contract = Contract::new();
+        let review = json!({"status": "REVIEW", "sponsor_requested_review": false, "reviewer_completed_attestation": false, "kyc_verified": false});
+        let payout = |amount: &str, currency: &str| {
+            json!({
+                "amount": amount, "currency": currency, "receiver": "bob.near",
+                "transaction_hash": "hash", "timestamp": "0", "milestone": null
+            })
+        };
+        let mut funded = review.clone();
+        funded["timeline_version"] = json!("V3");
+        funded["status"] = json!("FUNDED");
+        funded["test_transaction_sent"] = json!(true);
+        funded["request_for_trustees_created"] = json!(true);
+        funded["trustees_released_payment"] = json!(true);
+        funded["payouts"] = json!([payout("600", "USDC"), payout("100", "NEAR")]);
+        let mut legacy_funded = funded.clone();
+        legacy_funded["timeline_version"] = json!("V1");
+        legacy_funded["payouts"] = json!(["https://nearblocks.io/txns/hash"]);
+        let mut review_v1 = review.clone();
+        review_v1["timeline_version"] = json!("V1");
+
+        for (category, timeline) in
+            [("Marketing", funded), ("Marketing", review_v1), ("Events", legacy_funded)]
+        {
+            let body = proposal_body(json!({
+                "proposal_body_version": "V2",
+                "category": category,
+                "supervisor": "frol.near",
+                "timeline": timeline,
+                "linked_rfp": null,
+            }));
+            store_proposal(&mut contract, "bob.near", body);
+        }
+
+        let funding = serde_json::to_value(contract.get_proposal_funding(0)).unwrap();
+        assert_eq!(
+            funding["paid"],
+            json!([{"currency": "NEAR", "amount": "100"}, {"currency": "USDC", "amount": "600"}])
+        );
+
+        let legacy: Proposal = contract.get_proposal(2).into();
+        let timeline = legacy.snapshot.body.latest_version().timeline.latest_version();
+        let timeline = serde_json::to_value(timeline).unwrap();
+        assert_eq!(timeline["payouts"], json!([]));
+        assert_eq!(timeline["legacy_payouts"], json!(["https://nearblocks.io/txns/hash"]));
+
+        let page = contract.get_funding_by_category(None, None);
+        assert_eq!(page.next_cursor, None);
+        let categories = serde_json::to_value(page.categories).unwrap();
+        assert_eq!(categories[0]["category"], "Events");
+        assert_eq!(categories[0]["paid"], json!([]));
+        assert_eq!(categories[1]["approved_proposals"], 1);
+        assert_eq!(categories[1]["requested_usd_amount"], "1000");
+
+        let page = contract.get_funding_by_currency(None, Some(2));
+        assert_eq!(page.next_cursor, Some(2));
+        assert_eq!(
+            serde_json::to_value(page.currencies).unwrap(),
+            json!([
+                {"currency": "NEAR", "requested_usd_amount": "0", "paid_amount": "100"},
+                {"currency": "USDC", "requested_usd_amount": "1000", "paid_amount": "600"},
+            ])
+        );
+        let page = contract.get_funding_by_currency(Some(2), None);
+        assert_eq!(
+            serde_json::to_value(page.currencies).unwrap(),
+            json!([{"currency": "USDC", "requested_usd_amount": "1000", "paid_amount": "0"}])
+        );
+    }
+
+    fn pay(contract: &mut Contract, id: u32, receiver: &str, transaction_hash: &str) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("devhub.near".parse().unwrap())
+            .predecessor_account_id("devhub.near".parse().unwrap())
+            .build());
+        let timeline = json!({
+            "timeline_version": "V3", "status": "FUNDED", "sponsor_requested_review": true,
+            "reviewer_completed_attestation": true, "kyc_verified": true, "test_transaction_sent": true,
+            "request_for_trustees_created": true, "trustees_released_payment": true,
+            "payouts": [{
+                "amount": "1000", "currency": "USDC", "receiver": receiver,
+                "transaction_hash": transaction_hash, "timestamp": "0", "milestone": null
+            }],
+        });
+        contract.edit_proposal_versioned_timeline(id, serde_json::from_value(timeline).unwrap());
+    }
+
+    fn approved_contract() -> Contract {
+        testing_env!(VMContextBuilder::new().build());
+        let mut contract = Contract::new();
+        let approved = json!({"timeline_version": "V3", "status": "APPROVED", "sponsor_requested_review": true, "reviewer_completed_attestation": true, "kyc_verified": true});
+        let body = proposal_body(json!({
+            "proposal_body_version": "V2",
+            "supervisor": "frol.near",
+            "timeline": approved,
+            "linked_rfp": null,
+        }));
+        store_proposal(&mut contract, "bob.near", body.clone());
+        store_proposal(&mut contract, "bob.near", body);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "The payout transaction tx1 is already recorded")]
+    fn refuses_recorded_transactions() {
+        let mut contract = approved_contract();
+        pay(&mut contract, 0, "bob.near", "tx1");
+        let funding = serde_json::to_value(contract.get_proposal_funding(0)).unwrap();
+        assert_eq!(funding["paid"], json!([{"currency": "USDC", "amount": "1000"}]));
+        pay(&mut contract, 1, "bob.near", "tx1");
+    }
+
+    #[test]
+    #[should_panic(expected = "The payout receiver must be the receiver account of the proposal")]
+    fn refuses_payouts_to_other_accounts() {
+        let mut contract = approved_contract();
+        pay(&mut contract, 0, "eve.near", "hash");
+    }
+}
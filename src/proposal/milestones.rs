@@ -0,0 +1,325 @@
+use near_sdk::{env, near, require, Timestamp};
+
+use super::timeline::transitions::TimelineRole;
+use super::timeline::Payout;
+use super::{Proposal, ProposalBodyV3, ProposalId};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MilestoneStatus {
+    #[default]
+    Pending,
+    Submitted,
+    Accepted,
+    Paid,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct Milestone {
+    pub title: String,
+    pub description: String,
+    /// Amount in USD, like `requested_sponsorship_usd_amount`.
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub amount: u32,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub due_date: Timestamp,
+    #[serde(default)]
+    pub status: MilestoneStatus,
+    #[serde(default)]
+    pub payout: Option<Payout>,
+}
+
+impl Milestone {
+    fn same_terms(&self, other: &Milestone) -> bool {
+        self.title == other.title
+            && self.description == other.description
+            && self.amount == other.amount
+            && self.due_date == other.due_date
+    }
+}
+
+impl ProposalBodyV3 {
+    /// Checks that the amounts of the milestones add up to the requested amount. Proposals
+    /// without milestones are paid at once.
+    fn assert_milestone_amounts(&self) {
+        if self.milestones.is_empty() {
+            return;
+        }
+        let total: u64 = self.milestones.iter().map(|milestone| u64::from(milestone.amount)).sum();
+        require!(
+            total == u64::from(self.requested_sponsorship_usd_amount),
+            "The milestone amounts must add up to the requested amount"
+        );
+    }
+
+    /// Checks the milestones of a new proposal.
+    pub fn assert_new_milestones(&self) {
+        require!(
+            self.milestones.iter().all(|milestone| {
+                milestone.status == MilestoneStatus::Pending && milestone.payout.is_none()
+            }),
+            "The milestones of a new proposal must be pending"
+        );
+        self.assert_milestone_amounts();
+    }
+
+    /// Keeps the status and payout of the milestones from `previous`, since they are only changed
+    /// through the milestone methods. Milestones which are no longer pending cannot be changed or
+    /// removed, so they keep their position. The other milestones are pending.
+    pub fn settle_milestones(&mut self, previous: &ProposalBodyV3) {
+        for (index, milestone) in previous.milestones.iter().enumerate() {
+            require!(
+                milestone.status == MilestoneStatus::Pending
+                    || self.milestones.get(index).is_some_and(|other| other.same_terms(milestone)),
+                format!("Milestone {} is {:?} and cannot be changed", index, milestone.status)
+            );
+        }
+        for (index, milestone) in self.milestones.iter_mut().enumerate() {
+            match previous.milestones.get(index) {
+                Some(previous) => {
+                    milestone.status = previous.status;
+                    milestone.payout = previous.payout.clone();
+                }
+                None => {
+                    milestone.status = MilestoneStatus::Pending;
+                    milestone.payout = None;
+                }
+            }
+        }
+
+        let changed = self.requested_sponsorship_usd_amount
+            != previous.requested_sponsorship_usd_amount
+            || self.milestones.len() != previous.milestones.len()
+            || self.milestones.iter().zip(&previous.milestones).any(|(a, b)| !a.same_terms(b));
+        if changed {
+            self.assert_milestone_amounts();
+        }
+    }
+}
+
+impl Contract {
+    fn update_milestone(
+        &mut self,
+        proposal_id: ProposalId,
+        index: u32,
+        from: MilestoneStatus,
+        update: impl FnOnce(&mut Milestone),
+    ) -> ProposalId {
+        let proposal = self.migrate_proposal_history(proposal_id);
+        let mut body = proposal.snapshot.body.clone().latest_version();
+        let milestone = body.milestones.get_mut(index as usize).unwrap_or_else(|| {
+            panic!("Milestone {} of proposal id {} not found", index, proposal_id)
+        });
+        require!(
+            milestone.status == from,
+            format!("Milestone {} is {:?}, expected {:?}", index, milestone.status, from)
+        );
+        update(milestone);
+
+        let labels = proposal.snapshot.labels.clone();
+        let proposal_authors = self.get_proposal_authors(&proposal);
+        self.save_proposal_snapshot(proposal, body.into(), labels);
+        crate::notify::notify_edit_proposal(proposal_id, proposal_authors);
+        proposal_id
+    }
+
+    fn assert_milestone_role(&self, proposal: &Proposal, allowed: &[TimelineRole]) {
+        let roles = self.get_timeline_roles(proposal, &env::predecessor_account_id());
+        require!(
+            roles.iter().any(|role| *role == TimelineRole::Contract || allowed.contains(role)),
+            "The account is not allowed to update the milestones of this proposal"
+        );
+    }
+}
+
+#[near]
+impl Contract {
+    /// Marks a pending milestone of an approved proposal as done. Only the author can call it.
+    #[payable]
+    pub fn submit_milestone(&mut self, proposal_id: ProposalId, milestone: u32) -> ProposalId {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.assert_milestone_role(&proposal, &[TimelineRole::Author]);
+        let timeline = proposal.snapshot.body.latest_version().timeline.latest_version();
+        require!(
+            timeline.was_approved(),
+            "Milestones can only be submitted for approved proposals"
+        );
+
+        self.update_milestone(proposal_id, milestone, MilestoneStatus::Pending, |milestone| {
+            milestone.status = MilestoneStatus::Submitted;
+        })
+    }
+
+    #[payable]
+    pub fn accept_milestone(&mut self, proposal_id: ProposalId, milestone: u32) -> ProposalId {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.assert_milestone_role(&proposal, &[TimelineRole::Supervisor, TimelineRole::Moderator]);
+
+        self.update_milestone(proposal_id, milestone, MilestoneStatus::Submitted, |milestone| {
+            milestone.status = MilestoneStatus::Accepted;
+        })
+    }
+
+    #[payable]
+    pub fn record_milestone_payment(
+        &mut self,
+        proposal_id: ProposalId,
+        milestone: u32,
+        payout: Payout,
+    ) -> ProposalId {
+        let proposal: Proposal = self.get_proposal(proposal_id).into();
+        self.assert_milestone_role(&proposal, &[TimelineRole::Supervisor, TimelineRole::Moderator]);
+        self.assert_valid_payout(&proposal.snapshot.body.latest_version(), &payout);
+        self.payout_transactions.insert(&payout.transaction_hash, &proposal_id);
+
+        let index = milestone;
+        self.update_milestone(proposal_id, milestone, MilestoneStatus::Accepted, |milestone| {
+            milestone.status = MilestoneStatus::Paid;
+            milestone.payout = Some(Payout { milestone: Some(index), ..payout });
+        })
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::{Contract, Proposal, VersionedProposalBody};
+
+    fn milestones(contract: &Contract) -> serde_json::Value {
+        let proposal: Proposal = contract.get_proposal(0).into();
+        serde_json::to_value(proposal.snapshot.body.latest_version().milestones).unwrap()
+    }
+
+    fn milestone(title: &str, amount: &str) -> serde_json::Value {
+        json!({"title": title, "description": "", "amount": amount, "due_date": "100"})
+    }
+
+    fn body(timeline: serde_json::Value, milestones: serde_json::Value) -> VersionedProposalBody {
+        proposal_body(json!({
+            "proposal_body_version": "V3",
+            "supervisor": "frol.near",
+            "timeline": timeline,
+            "linked_rfp": null,
+            "milestones": milestones,
+        }))
+    }
+
+    fn approved() -> serde_json::Value {
+        json!({
+            "timeline_version": "V3", "status": "APPROVED", "sponsor_requested_review": true,
+            "reviewer_completed_attestation": true, "kyc_verified": true
+        })
+    }
+
+    #[test]
+    fn milestone_lifecycle() {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(
+            &mut contract,
+            "bob.near",
+            body(approved(), json!([milestone("first", "500"), milestone("second", "500")])),
+        );
+
+        set_predecessor("bob.near");
+        contract.submit_milestone(0, 1);
+        set_predecessor("frol.near");
+        contract.accept_milestone(0, 1);
+        contract.record_milestone_payment(
+            0,
+            1,
+            serde_json::from_value(json!({
+                "amount": "498", "currency": "USDC", "receiver": "bob.near",
+                "transaction_hash": "hash", "timestamp": "200", "milestone": null
+            }))
+            .unwrap(),
+        );
+
+        let value = milestones(&contract);
+        assert_eq!(value[0]["status"], "PENDING");
+        assert_eq!(value[1]["status"], "PAID");
+        assert_eq!(value[1]["payout"]["milestone"], 1);
+        assert_eq!(contract.get_proposal_history(0, None, None).len(), 3);
+
+        let funding = serde_json::to_value(contract.get_proposal_funding(0)).unwrap();
+        assert_eq!(funding["paid"], json!([{"currency": "USDC", "amount": "498"}]));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The account is not allowed to update the milestones of this proposal"
+    )]
+    fn only_author_submits() {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(
+            &mut contract,
+            "bob.near",
+            body(
+                json!({"timeline_version": "V3", "status": "DRAFT"}),
+                json!([milestone("first", "500"), milestone("second", "500")]),
+            ),
+        );
+
+        set_predecessor("frol.near");
+        contract.submit_milestone(0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Milestone 0 is Submitted and cannot be changed")]
+    fn keeps_started_milestones() {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(
+            &mut contract,
+            "bob.near",
+            body(approved(), json!([milestone("first", "500"), milestone("second", "500")])),
+        );
+
+        set_predecessor("bob.near");
+        contract.submit_milestone(0, 0);
+        set_predecessor("devhub.near");
+        contract.edit_proposal(
+            0,
+            body(approved(), json!([milestone("second", "500"), milestone("first", "500")])),
+            Default::default(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The milestone amounts must add up to the requested amount")]
+    fn checks_milestone_amounts() {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(
+            &mut contract,
+            "bob.near",
+            body(
+                json!({"timeline_version": "V3", "status": "DRAFT"}),
+                json!([milestone("first", "500"), milestone("second", "500")]),
+            ),
+        );
+
+        set_predecessor("bob.near");
+        contract.edit_proposal(
+            0,
+            body(
+                json!({"timeline_version": "V3", "status": "DRAFT"}),
+                json!([milestone("first", "500"), milestone("second", "600")]),
+            ),
+            Default::default(),
+        );
+    }
+}
@@ -1,10 +1,28 @@
+pub mod budgets;
+pub mod categories;
+pub mod coauthors;
+pub mod conflicts;
+pub mod diff;
+pub mod funding;
+pub mod milestones;
+pub mod query;
 pub mod repost;
+pub mod review;
+pub mod similarity;
 pub mod timeline;
+pub mod transfer;
+pub mod voting;
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
-use self::timeline::{TimelineStatusV1, VersionedTimelineStatus};
+use self::categories::ProposalCategory;
+use self::milestones::Milestone;
+use self::timeline::transitions::{assert_transition_allowed, TimelineRole};
+use self::voting::VoteTally;
+use self::timeline::{TimelineStatus, TimelineStatusV1, VersionedTimelineStatus};
 
+use crate::comment::CommentTarget;
 use crate::Contract;
 use crate::str_serializers::*;
 use crate::{notify::get_text_mentions, rfp::RFPId};
@@ -15,16 +33,23 @@ pub type ProposalId = u32;
 
 type PostTag = String;
 
+/// Number of a proposal snapshot. The first snapshot of a proposal has revision 0 and every edit
+/// increments it.
+pub type ProposalRevision = u32;
+
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
 #[serde(tag = "proposal_version")]
 pub enum VersionedProposal {
-    V0(Proposal),
+    V0(ProposalV0),
+    V1(Proposal),
 }
 
+/// Layout of proposals from before the snapshot history was moved into
+/// `Contract::proposal_revisions`.
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
-pub struct Proposal {
+pub struct ProposalV0 {
     pub id: ProposalId,
     pub author_id: AccountId,
     #[serde(
@@ -37,17 +62,97 @@ pub struct Proposal {
     pub snapshot_history: Vec<ProposalSnapshot>,
 }
 
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct Proposal {
+    pub id: ProposalId,
+    pub author_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub social_db_post_block_height: BlockHeight,
+    pub snapshot: ProposalSnapshot,
+    // Revision of the current snapshot. Earlier revisions are stored in
+    // `Contract::proposal_revisions`.
+    pub revision: ProposalRevision,
+}
+
+impl From<ProposalV0> for Proposal {
+    fn from(v0: ProposalV0) -> Self {
+        Proposal {
+            id: v0.id,
+            author_id: v0.author_id,
+            social_db_post_block_height: v0.social_db_post_block_height,
+            snapshot: v0.snapshot,
+            revision: v0.snapshot_history.len().try_into().unwrap(),
+        }
+    }
+}
+
 impl From<VersionedProposal> for Proposal {
     fn from(vp: VersionedProposal) -> Self {
         match vp {
-            VersionedProposal::V0(v0) => v0,
+            VersionedProposal::V0(v0) => v0.into(),
+            VersionedProposal::V1(v1) => v1,
         }
     }
 }
 
 impl From<Proposal> for VersionedProposal {
     fn from(p: Proposal) -> Self {
-        VersionedProposal::V0(p)
+        VersionedProposal::V1(p)
+    }
+}
+
+/// Lightweight view of the current state of a proposal which omits the description and the
+/// snapshot history.
+#[near(serializers=[json])]
+#[derive(Clone)]
+pub struct ProposalSummary {
+    pub id: ProposalId,
+    pub author_id: AccountId,
+    pub name: String,
+    pub category: String,
+    pub summary: String,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub requested_sponsorship_usd_amount: u32,
+    pub requested_sponsorship_paid_in_currency: ProposalFundingCurrency,
+    pub timeline: TimelineStatus,
+    pub labels: HashSet<PostTag>,
+    pub linked_rfp: Option<RFPId>,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub history_length: u32,
+    pub comment_count: u32,
+    pub votes: VoteTally,
+}
+
+impl ProposalSummary {
+    pub fn new(proposal: Proposal, comment_count: u32, votes: VoteTally) -> Self {
+        let body = proposal.snapshot.body.latest_version();
+        ProposalSummary {
+            id: proposal.id,
+            author_id: proposal.author_id,
+            name: body.name,
+            category: body.category,
+            summary: body.summary,
+            requested_sponsorship_usd_amount: body.requested_sponsorship_usd_amount,
+            requested_sponsorship_paid_in_currency: body.requested_sponsorship_paid_in_currency,
+            timeline: body.timeline.latest_version(),
+            labels: proposal.snapshot.labels,
+            linked_rfp: body.linked_rfp,
+            timestamp: proposal.snapshot.timestamp,
+            history_length: proposal.revision,
+            comment_count,
+            votes,
+        }
     }
 }
 
@@ -127,6 +232,30 @@ pub struct ProposalBodyV2 {
     pub linked_rfp: Option<RFPId>,
 }
 
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalBodyV3 {
+    pub name: String,
+    pub category: String,
+    pub summary: String,
+    pub description: String,
+    pub linked_proposals: Vec<ProposalId>,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub requested_sponsorship_usd_amount: u32,
+    pub requested_sponsorship_paid_in_currency: ProposalFundingCurrency,
+    pub receiver_account: AccountId,
+    pub requested_sponsor: AccountId,
+    pub supervisor: Option<AccountId>,
+    pub timeline: VersionedTimelineStatus,
+    pub linked_rfp: Option<RFPId>,
+    /// Tranches the sponsorship is paid in. Empty when it is paid at once.
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+}
+
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
 #[serde(tag = "proposal_body_version")]
@@ -134,6 +263,7 @@ pub enum VersionedProposalBody {
     V0(ProposalBodyV0),
     V1(ProposalBodyV1),
     V2(ProposalBodyV2),
+    V3(ProposalBodyV3),
 }
 
 impl From<ProposalBodyV0> for ProposalBodyV1 {
@@ -193,6 +323,26 @@ impl From<VersionedProposalBody> for ProposalBodyV1 {
     }
 }
 
+impl From<ProposalBodyV2> for ProposalBodyV3 {
+    fn from(v2: ProposalBodyV2) -> Self {
+        ProposalBodyV3 {
+            name: v2.name,
+            category: v2.category,
+            summary: v2.summary,
+            description: v2.description,
+            linked_proposals: v2.linked_proposals,
+            requested_sponsorship_usd_amount: v2.requested_sponsorship_usd_amount,
+            requested_sponsorship_paid_in_currency: v2.requested_sponsorship_paid_in_currency,
+            receiver_account: v2.receiver_account,
+            requested_sponsor: v2.requested_sponsor,
+            supervisor: v2.supervisor,
+            timeline: v2.timeline,
+            linked_rfp: v2.linked_rfp,
+            milestones: vec![],
+        }
+    }
+}
+
 impl From<VersionedProposalBody> for ProposalBodyV2 {
     fn from(solution: VersionedProposalBody) -> Self {
         match solution {
@@ -202,6 +352,16 @@ impl From<VersionedProposalBody> for ProposalBodyV2 {
             },
             VersionedProposalBody::V1(v1) => v1.into(),
             VersionedProposalBody::V2(v2) => v2,
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl From<VersionedProposalBody> for ProposalBodyV3 {
+    fn from(solution: VersionedProposalBody) -> Self {
+        match solution {
+            VersionedProposalBody::V3(v3) => v3,
+            _ => ProposalBodyV2::from(solution).into(),
         }
     }
 }
@@ -224,13 +384,19 @@ impl From<ProposalBodyV2> for VersionedProposalBody {
     }
 }
 
+impl From<ProposalBodyV3> for VersionedProposalBody {
+    fn from(p: ProposalBodyV3) -> Self {
+        VersionedProposalBody::V3(p)
+    }
+}
+
 impl VersionedProposalBody {
-    pub fn latest_version(self) -> ProposalBodyV2 {
+    pub fn latest_version(self) -> ProposalBodyV3 {
         self.into()
     }
 }
 
-pub fn get_subscribers(proposal_body: &ProposalBodyV2) -> Vec<String> {
+pub fn get_subscribers(proposal_body: &ProposalBodyV3) -> Vec<String> {
     let mut result = [
         get_text_mentions(proposal_body.description.as_str()),
         get_text_mentions(proposal_body.summary.as_str()),
@@ -243,20 +409,23 @@ pub fn get_subscribers(proposal_body: &ProposalBodyV2) -> Vec<String> {
     result
 }
 
-pub fn default_categories() -> Vec<String> {
-    vec![
-        String::from("DevDAO Operations"),
-        String::from("Decentralized DevRel"),
-        String::from("NEAR Campus"),
-        String::from("Marketing"),
-        String::from("Events"),
-        String::from("Tooling & Infrastructures"),
-        String::from("Other"),
+pub fn default_categories() -> Vec<ProposalCategory> {
+    [
+        "DevDAO Operations",
+        "Decentralized DevRel",
+        "NEAR Campus",
+        "Marketing",
+        "Events",
+        "Tooling & Infrastructures",
+        "Other",
     ]
+    .into_iter()
+    .map(|name| ProposalCategory::new(name.to_string()))
+    .collect()
 }
 
 #[near(serializers=[borsh, json])]
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ProposalFundingCurrency {
     NEAR,
     USDT,
@@ -265,6 +434,108 @@ pub enum ProposalFundingCurrency {
 }
 
 impl Contract {
+    /// Returns the snapshot of the proposal at the given revision. Proposals which were not
+    /// migrated to the revisions store yet are served from their inline history.
+    pub(crate) fn get_proposal_snapshot(
+        &self,
+        id: ProposalId,
+        revision: ProposalRevision,
+    ) -> Option<ProposalSnapshot> {
+        match self.proposals.get(id.into())? {
+            VersionedProposal::V0(mut v0) => {
+                let revision: usize = revision.try_into().unwrap();
+                match revision.cmp(&v0.snapshot_history.len()) {
+                    Ordering::Less => Some(v0.snapshot_history.swap_remove(revision)),
+                    Ordering::Equal => Some(v0.snapshot),
+                    Ordering::Greater => None,
+                }
+            }
+            VersionedProposal::V1(v1) => {
+                match revision.cmp(&v1.revision) {
+                    Ordering::Less => self.proposal_revisions.get(&(id, revision)),
+                    Ordering::Equal => Some(v1.snapshot),
+                    Ordering::Greater => None,
+                }
+            }
+        }
+    }
+
+    /// Moves the inline snapshot history of a proposal stored in the old layout into
+    /// `proposal_revisions` and returns the proposal in the current layout.
+    pub(crate) fn migrate_proposal_history(&mut self, id: ProposalId) -> Proposal {
+        let versioned_proposal = self
+            .proposals
+            .get(id.into())
+            .unwrap_or_else(|| panic!("Proposal id {} not found", id));
+        match versioned_proposal {
+            VersionedProposal::V0(v0) => {
+                for (revision, snapshot) in v0.snapshot_history.iter().enumerate() {
+                    self.proposal_revisions.insert(&(id, revision.try_into().unwrap()), snapshot);
+                }
+                let proposal: Proposal = v0.into();
+                self.proposals.replace(id.into(), &proposal.clone().into());
+                proposal
+            }
+            VersionedProposal::V1(v1) => v1,
+        }
+    }
+
+    pub(crate) fn get_proposal_summary(&self, proposal: Proposal) -> ProposalSummary {
+        let comment_count = self.get_comment_count(CommentTarget::Proposal(proposal.id));
+        let votes = self.get_vote_tally(proposal.id);
+        ProposalSummary::new(proposal, comment_count, votes)
+    }
+
+    /// Makes `body` and `labels` the current snapshot of the proposal and moves the previous one
+    /// to `proposal_revisions`.
+    pub(crate) fn save_proposal_snapshot(
+        &mut self,
+        mut proposal: Proposal,
+        body: VersionedProposalBody,
+        labels: HashSet<String>,
+    ) {
+        let new_snapshot = ProposalSnapshot {
+            editor_id: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+            labels,
+            body,
+        };
+        let old_snapshot = std::mem::replace(&mut proposal.snapshot, new_snapshot);
+        self.proposal_revisions.insert(&(proposal.id, proposal.revision), &old_snapshot);
+        proposal.revision += 1;
+        self.proposals.replace(proposal.id.into(), &proposal.into());
+    }
+
+    pub(crate) fn get_timeline_roles(
+        &self,
+        proposal: &Proposal,
+        account_id: &AccountId,
+    ) -> Vec<TimelineRole> {
+        let body = proposal.snapshot.body.clone().latest_version();
+        // Accounts which declared a conflict keep only the role of an author.
+        let recused = self.has_declared_conflict(proposal.id, account_id);
+        let mut roles = vec![];
+        if self.is_allowed_to_edit_proposal(proposal.id, Some(account_id.clone())) {
+            roles.push(TimelineRole::Author);
+        }
+        if body.supervisor.as_ref() == Some(account_id) && !recused {
+            roles.push(TimelineRole::Supervisor);
+        }
+        if &body.requested_sponsor == account_id && !recused {
+            roles.push(TimelineRole::RequestedSponsor);
+        }
+        if self.has_moderator(account_id.clone())
+            && !recused
+            && !self.is_self_interested(proposal, account_id)
+        {
+            roles.push(TimelineRole::Moderator);
+        }
+        if account_id == &env::current_account_id() {
+            roles.push(TimelineRole::Contract);
+        }
+        roles
+    }
+
     pub(crate) fn update_proposal_labels(&mut self, proposal_id: ProposalId, new_labels: HashSet<String>) -> ProposalId {
         let proposal: Proposal = self
             .proposals
@@ -275,61 +546,116 @@ impl Contract {
         self.edit_proposal_internal(proposal_id, proposal.snapshot.body, new_labels)
     }
 
+    /// Checks and records the move of the proposal from the timeline of `old_body` to
+    /// `new_timeline`: the voting quorum, the review rounds, the sign-offs of `editor_id`, the
+    /// supervisor and the category budget. Shared by the edits and the review decisions, the
+    /// callers check who may make the move.
+    pub(crate) fn settle_timeline_change(
+        &mut self,
+        id: ProposalId,
+        body: &ProposalBodyV3,
+        old_body: &ProposalBodyV3,
+        new_timeline: &mut TimelineStatus,
+        editor_id: &AccountId,
+    ) {
+        let current_timeline = old_body.timeline.clone().latest_version();
+        self.assert_voting_quorum(id, &body.category, &current_timeline, new_timeline);
+        if new_timeline.is_review() && !current_timeline.is_review() {
+            self.open_voting_round(id);
+            self.open_review_round(id);
+        }
+        new_timeline.settle_sign_offs(&current_timeline, editor_id, env::block_timestamp());
+
+        require!(
+            new_timeline.is_draft() ||  new_timeline.is_review() || new_timeline.is_cancelled() || body.supervisor.is_some(),
+            "You can't change the timeline of the proposal to this status without adding a supervisor"
+        );
+
+        self.assert_category_allows(body, Some(old_body));
+        self.settle_category_budget(
+            id,
+            &body.category,
+            body.requested_sponsorship_usd_amount,
+            current_timeline.kind(),
+            new_timeline.kind(),
+        );
+    }
+
     pub(crate) fn edit_proposal_internal(
         &mut self,
         id: ProposalId,
         body: VersionedProposalBody,
         labels: HashSet<String>,
     ) -> ProposalId {
-        require!(
-            self.is_allowed_to_edit_proposal(id, Option::None),
-            "The account is not allowed to edit this proposal"
-        );
+        let proposal_authors = self.save_proposal_edit(id, body, labels);
+        crate::notify::notify_edit_proposal(id, proposal_authors);
+        id
+    }
+
+    /// Checks and saves the edit of the proposal without notifying anyone. Returns the authors
+    /// of the proposal to notify.
+    pub(crate) fn save_proposal_edit(
+        &mut self,
+        id: ProposalId,
+        body: VersionedProposalBody,
+        labels: HashSet<String>,
+    ) -> Vec<AccountId> {
         let editor_id = env::predecessor_account_id();
-        let mut proposal: Proposal = self
-            .proposals
-            .get(id.into())
-            .unwrap_or_else(|| panic!("Proposal id {} not found", id))
-            .into();
+        let proposal = self.migrate_proposal_history(id);
+        let roles = self.get_timeline_roles(&proposal, &editor_id);
 
         let proposal_body = body.clone().latest_version();
-
         let old_body = proposal.snapshot.body.clone();
-        let labels = self.update_and_check_rfp_link(id, body.clone(), Some(old_body.clone()), labels);
-
-        let current_timeline = old_body.latest_version().timeline.latest_version();
-        let new_timeline = proposal_body.timeline.latest_version();
-
+        let old_body_latest = old_body.clone().latest_version();
+
+        // Supervisors and requested sponsors may only move the proposal along its timeline.
+        let only_timeline_changed = {
+            let mut unchanged_body = proposal_body.clone();
+            unchanged_body.timeline = old_body_latest.timeline.clone();
+            near_sdk::serde_json::to_value(&unchanged_body).ok()
+                == near_sdk::serde_json::to_value(&old_body_latest).ok()
+                && labels == proposal.snapshot.labels
+        };
         require!(
-            self.has_moderator(editor_id.clone())
-                || editor_id.clone() == env::current_account_id()
-                || current_timeline.is_draft()
-                    && (new_timeline.is_empty_review()
-                        || new_timeline.is_draft())
-                || current_timeline.can_be_cancelled() && new_timeline.is_cancelled(),
-            "This account is only allowed to change proposal status from DRAFT to REVIEW"
+            roles.contains(&TimelineRole::Author) || only_timeline_changed && !roles.is_empty(),
+            "The account is not allowed to edit this proposal"
         );
-
         require!(
-            new_timeline.is_draft() ||  new_timeline.is_review() || new_timeline.is_cancelled() || proposal_body.supervisor.is_some(),
-            "You can't change the timeline of the proposal to this status without adding a supervisor"
+            proposal_body.receiver_account == old_body_latest.receiver_account
+                || !self.is_proposal_coauthor(id, &editor_id),
+            "Co-authors cannot change the receiver account"
         );
 
-        require!(self.proposal_categories.contains(&proposal_body.category), "Unknown category");
+        let labels = self.update_and_check_rfp_link(id, body.clone(), Some(old_body.clone()), labels);
+
+        let current_timeline = old_body_latest.timeline.clone().latest_version();
+        let mut new_timeline = proposal_body.timeline.clone().latest_version();
+        assert_transition_allowed(&current_timeline, &new_timeline, &roles);
+        self.settle_timeline_change(
+            id,
+            &proposal_body,
+            &old_body_latest,
+            &mut new_timeline,
+            &editor_id,
+        );
 
-        let old_snapshot = proposal.snapshot.clone();
-        let old_labels_set = old_snapshot.labels.clone();
+        let mut proposal_body = proposal_body;
+        proposal_body.timeline = new_timeline.into();
+        proposal_body.settle_milestones(&old_body_latest);
+        self.settle_payouts(id, &proposal_body, &old_body_latest);
+
+        let old_labels_set = proposal.snapshot.labels.clone();
         let new_labels = labels;
-        let new_snapshot = ProposalSnapshot {
-            editor_id: editor_id.clone(),
-            timestamp: env::block_timestamp(),
-            labels: new_labels.clone(),
-            body: body,
-        };
-        proposal.snapshot = new_snapshot;
-        proposal.snapshot_history.push(old_snapshot);
-        let proposal_author = proposal.author_id.clone();
-        self.proposals.replace(id.try_into().unwrap(), &proposal.into());
+        let proposal_authors = self.get_proposal_authors(&proposal);
+        let text_changed = proposal_body.name != old_body_latest.name
+            || proposal_body.summary != old_body_latest.summary
+            || proposal_body.description != old_body_latest.description;
+        self.save_proposal_snapshot(proposal, proposal_body.into(), new_labels.clone());
+        if text_changed {
+            let proposal: Proposal = self.get_proposal(id).into();
+            self.index_proposal_fingerprint(&proposal);
+            self.index_proposal_search(&proposal);
+        }
 
         // Update labels index.
         let new_labels_set = new_labels;
@@ -362,7 +688,6 @@ impl Contract {
             self.label_to_proposals.insert(&label_to_add, &proposals);
         }
 
-        crate::notify::notify_edit_proposal(id, proposal_author);
-        id
+        proposal_authors
     }
 }
\ No newline at end of file
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+
+use near_sdk::json_types::U64;
+use near_sdk::{near, AccountId};
+
+use super::timeline::TimelineStatusKind;
+use super::{Proposal, ProposalId, ProposalSummary, VersionedProposal};
+use crate::rfp::RFPId;
+use crate::Contract;
+
+/// Default number of proposals returned by a single page.
+pub const DEFAULT_PAGE_LIMIT: u32 = 20;
+/// Upper bound on the number of proposals returned by a single page.
+pub const MAX_PAGE_LIMIT: u32 = 100;
+/// Upper bound on the number of proposals loaded from storage while building a single page, so
+/// that selective filters cannot exhaust the view call gas. The page is cut short and a cursor is
+/// returned when the limit is reached.
+pub const MAX_PAGE_SCAN: u32 = 500;
+
+#[near(serializers=[json])]
+#[derive(Clone, Default)]
+pub struct ProposalFilters {
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub author_id: Option<AccountId>,
+    /// Only proposals that have all of these labels.
+    #[serde(default)]
+    pub labels: Option<Vec<String>>,
+    #[serde(default)]
+    pub status: Option<TimelineStatusKind>,
+    #[serde(default)]
+    pub linked_rfp: Option<RFPId>,
+    #[serde(default)]
+    pub created_after: Option<U64>,
+    #[serde(default)]
+    pub created_before: Option<U64>,
+    #[serde(default)]
+    pub updated_after: Option<U64>,
+    #[serde(default)]
+    pub updated_before: Option<U64>,
+}
+
+#[near(serializers=[json])]
+pub struct ProposalsPage {
+    pub proposals: Vec<VersionedProposal>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more proposals.
+    pub next_cursor: Option<ProposalId>,
+}
+
+#[near(serializers=[json])]
+pub struct ProposalSummariesPage {
+    pub proposals: Vec<ProposalSummary>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more proposals.
+    pub next_cursor: Option<ProposalId>,
+}
+
+impl ProposalFilters {
+    /// Checks the filters which cannot be answered by the label and author indexes.
+    /// `created_timestamp` is only evaluated when a creation time filter is set.
+    pub fn matches(&self, proposal: &Proposal, created_timestamp: impl FnOnce() -> u64) -> bool {
+        let body = proposal.snapshot.body.clone().latest_version();
+        if let Some(category) = &self.category {
+            if &body.category != category {
+                return false;
+            }
+        }
+        if let Some(author_id) = &self.author_id {
+            if &proposal.author_id != author_id {
+                return false;
+            }
+        }
+        if let Some(labels) = &self.labels {
+            if !labels.iter().all(|label| proposal.snapshot.labels.contains(label)) {
+                return false;
+            }
+        }
+        if let Some(status) = self.status {
+            if body.timeline.latest_version().kind() != status {
+                return false;
+            }
+        }
+        if self.linked_rfp.is_some() && body.linked_rfp != self.linked_rfp {
+            return false;
+        }
+        let updated = proposal.snapshot.timestamp;
+        if !(self.updated_after.map_or(true, |after| updated >= after.0)
+            && self.updated_before.map_or(true, |before| updated < before.0))
+        {
+            return false;
+        }
+        if self.created_after.is_none() && self.created_before.is_none() {
+            return true;
+        }
+        let created = created_timestamp();
+        self.created_after.map_or(true, |after| created >= after.0)
+            && self.created_before.map_or(true, |before| created < before.0)
+    }
+}
+
+impl Contract {
+    /// Narrows down the proposal ids to look at using the author and label indexes.
+    /// Returns `None` when no indexed filter is set and every proposal is a candidate.
+    fn proposal_candidates(&self, filters: &ProposalFilters) -> Option<Vec<ProposalId>> {
+        let mut candidates: Option<HashSet<ProposalId>> = None;
+        if let Some(author_id) = &filters.author_id {
+            candidates = Some(self.author_proposals.get(author_id).unwrap_or_default());
+        }
+        for label in filters.labels.iter().flatten() {
+            let labeled = self.label_to_proposals.get(label).unwrap_or_default();
+            candidates = Some(match candidates {
+                Some(candidates) => &candidates & &labeled,
+                None => labeled,
+            });
+        }
+        candidates.map(|candidates| {
+            let mut res: Vec<_> = candidates.into_iter().collect();
+            res.sort();
+            res
+        })
+    }
+
+    pub(crate) fn query_proposals(
+        &self,
+        cursor: Option<ProposalId>,
+        limit: Option<u32>,
+        filters: ProposalFilters,
+    ) -> (Vec<Proposal>, Option<ProposalId>) {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+        let cursor = cursor.unwrap_or(0);
+        let total: ProposalId = self.proposals.len().try_into().unwrap();
+
+        let candidates: Box<dyn Iterator<Item = ProposalId>> = match self
+            .proposal_candidates(&filters)
+        {
+            Some(ids) => Box::new(ids.into_iter().filter(move |id| *id >= cursor && *id < total)),
+            None => Box::new(cursor..total),
+        };
+
+        let mut result = vec![];
+        let mut scanned = 0;
+        let mut candidates = candidates.peekable();
+        while let Some(&id) = candidates.peek() {
+            if result.len() >= limit || scanned >= MAX_PAGE_SCAN {
+                return (result, Some(id));
+            }
+            candidates.next();
+            scanned += 1;
+            let proposal: Proposal = self.proposals.get(id.into()).unwrap().into();
+            let created_timestamp = || {
+                self.get_proposal_snapshot(id, 0).map_or(proposal.snapshot.timestamp, |s| s.timestamp)
+            };
+            if filters.matches(&proposal, created_timestamp) {
+                result.push(proposal);
+            }
+        }
+        (result, None)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, AccountId};
+
+    use super::ProposalFilters;
+    use crate::proposal::timeline::TimelineStatusKind;
+    use crate::{Contract, Proposal, ProposalBodyV0, ProposalSnapshot, VersionedProposalBody};
+
+    fn push_proposal(
+        contract: &mut Contract,
+        author: &str,
+        category: &str,
+        labels: &[&str],
+        status: &str,
+        timestamp: u64,
+    ) {
+        let id = contract.proposals.len() as u32;
+        let author_id: AccountId = author.parse().unwrap();
+        let body: ProposalBodyV0 = near_sdk::serde_json::from_value(json!({
+            "name": format!("Proposal {}", id),
+            "description": "description",
+            "category": category,
+            "summary": "summary",
+            "linked_proposals": [],
+            "requested_sponsorship_usd_amount": "1000",
+            "requested_sponsorship_paid_in_currency": "USDT",
+            "receiver_account": author,
+            "supervisor": "frol.near",
+            "requested_sponsor": "neardevdao.near",
+            "timeline": {"status": status, "sponsor_requested_review": false, "reviewer_completed_attestation": false}
+        }))
+        .unwrap();
+        let labels: HashSet<String> = labels.iter().map(|label| label.to_string()).collect();
+        for label in &labels {
+            let mut proposals = contract.label_to_proposals.get(label).unwrap_or_default();
+            proposals.insert(id);
+            contract.label_to_proposals.insert(label, &proposals);
+        }
+        let mut author_proposals = contract.author_proposals.get(&author_id).unwrap_or_default();
+        author_proposals.insert(id);
+        contract.author_proposals.insert(&author_id, &author_proposals);
+        let proposal = Proposal {
+            id,
+            author_id: author_id.clone(),
+            social_db_post_block_height: 0,
+            snapshot: ProposalSnapshot {
+                editor_id: author_id,
+                timestamp,
+                labels,
+                body: VersionedProposalBody::V0(body),
+            },
+            revision: 0,
+        };
+        contract.proposals.push(&proposal.into());
+    }
+
+    fn setup() -> Contract {
+        testing_env!(VMContextBuilder::new().build());
+        let mut contract = Contract::new();
+        push_proposal(&mut contract, "alice.near", "Marketing", &["a", "b"], "DRAFT", 10);
+        push_proposal(&mut contract, "bob.near", "Events", &["a"], "REVIEW", 20);
+        push_proposal(&mut contract, "alice.near", "Events", &["b"], "REVIEW", 30);
+        push_proposal(&mut contract, "alice.near", "Marketing", &["a", "b"], "REVIEW", 40);
+        push_proposal(&mut contract, "bob.near", "Marketing", &[], "DRAFT", 50);
+        contract
+    }
+
+    fn ids(page: &super::ProposalsPage) -> Vec<u32> {
+        page.proposals.iter().map(|p| Proposal::from(p.clone()).id).collect()
+    }
+
+    #[test]
+    fn paginates_without_filters() {
+        let contract = setup();
+        let page = contract.get_proposals_page(None, Some(2), None);
+        assert_eq!(ids(&page), vec![0, 1]);
+        assert_eq!(page.next_cursor, Some(2));
+
+        let page = contract.get_proposals_page(Some(4), Some(2), None);
+        assert_eq!(ids(&page), vec![4]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn filters_by_indexes_and_snapshot() {
+        let contract = setup();
+        let filters = ProposalFilters {
+            author_id: Some("alice.near".parse().unwrap()),
+            labels: Some(vec!["a".to_string(), "b".to_string()]),
+            ..Default::default()
+        };
+        let page = contract.get_proposals_page(None, None, Some(filters));
+        assert_eq!(ids(&page), vec![0, 3]);
+
+        let filters = ProposalFilters {
+            category: Some("Marketing".to_string()),
+            status: Some(TimelineStatusKind::Review),
+            ..Default::default()
+        };
+        assert_eq!(ids(&contract.get_proposals_page(None, None, Some(filters))), vec![3]);
+
+        let filters = ProposalFilters {
+            updated_after: Some(20.into()),
+            updated_before: Some(50.into()),
+            ..Default::default()
+        };
+        let page = contract.get_proposals_page(None, Some(1), Some(filters.clone()));
+        assert_eq!(ids(&page), vec![1]);
+        let page = contract.get_proposals_page(page.next_cursor, Some(5), Some(filters));
+        assert_eq!(ids(&page), vec![2, 3]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn summaries_omit_history() {
+        let contract = setup();
+        let filters = ProposalFilters {
+            author_id: Some("bob.near".parse().unwrap()),
+            ..Default::default()
+        };
+        let page = contract.get_proposal_summaries_page(None, None, Some(filters));
+        let summaries: Vec<_> = page.proposals.iter().map(|p| (p.id, p.timestamp)).collect();
+        assert_eq!(summaries, vec![(1, 20), (4, 50)]);
+
+        let summary = near_sdk::serde_json::to_value(&page.proposals[0]).unwrap();
+        assert_eq!(summary["category"], "Events");
+        assert_eq!(summary["timeline"]["status"], "REVIEW");
+        assert_eq!(summary["history_length"], 0);
+        assert!(summary.get("description").is_none());
+
+        let summaries = contract.get_proposal_summaries(vec![3, 42]);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].name, "Proposal 3");
+    }
+}
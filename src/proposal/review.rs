@@ -0,0 +1,387 @@
+use std::collections::{HashMap, HashSet};
+
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::timeline::TimelineStatus;
+use super::{ProposalId, ProposalRevision};
+use crate::common::charge_storage_deposit;
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+/// Maximum number of characters of the rationale of a review.
+pub const MAX_RATIONALE_LENGTH: usize = 2000;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReviewDecision {
+    Approve,
+    Reject,
+    ApproveConditionally,
+}
+
+impl ReviewDecision {
+    /// Status the proposal moves to once enough reviewers agree on the decision.
+    fn outcome(self, review: &TimelineStatus) -> TimelineStatus {
+        let review_status = review.get_review_status().clone();
+        match self {
+            ReviewDecision::Approve => TimelineStatus::Approved(review_status),
+            ReviewDecision::Reject => TimelineStatus::Rejected(review_status),
+            ReviewDecision::ApproveConditionally => {
+                TimelineStatus::ApprovedConditionally(review_status)
+            }
+        }
+    }
+}
+
+/// Reviewers of the proposals of a category and the number of matching sign-offs which settle
+/// the review.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ReviewPolicy {
+    /// Names of the `access_control` teams whose members, including the members of their
+    /// sub-teams, review the proposals.
+    pub reviewer_teams: HashSet<String>,
+    pub approve_threshold: u32,
+    pub reject_threshold: u32,
+    pub approve_conditionally_threshold: u32,
+}
+
+impl ReviewPolicy {
+    fn threshold(&self, decision: ReviewDecision) -> u32 {
+        match decision {
+            ReviewDecision::Approve => self.approve_threshold,
+            ReviewDecision::Reject => self.reject_threshold,
+            ReviewDecision::ApproveConditionally => self.approve_conditionally_threshold,
+        }
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ReviewRecord {
+    pub reviewer_id: AccountId,
+    pub decision: ReviewDecision,
+    pub rationale: String,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    /// Revision of the proposal the review was submitted for. The review no longer counts once
+    /// the proposal is edited.
+    pub revision: ProposalRevision,
+}
+
+/// Decision log of a proposal. It is kept in `proposal_reviews` rather than in the proposal
+/// snapshots, so that reviewing doesn't create proposal revisions and the log isn't copied into
+/// every snapshot of the proposal.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default)]
+pub struct ProposalReviews {
+    pub log: Vec<ReviewRecord>,
+    /// Index in `log` of the first review of the current review round. Reviews of the earlier
+    /// rounds are kept but no longer count.
+    pub round_start: u32,
+}
+
+impl ProposalReviews {
+    /// Number of reviewers per decision in the current round, counting only the latest review of
+    /// each reviewer for the `revision` of the proposal and skipping the reviewers for which
+    /// `recused` holds.
+    pub fn current_decisions(
+        &self,
+        revision: ProposalRevision,
+        recused: impl Fn(&AccountId) -> bool,
+    ) -> HashMap<ReviewDecision, u32> {
+        let latest: HashMap<&AccountId, ReviewDecision> = self.log[self.round_start as usize..]
+            .iter()
+            .filter(|record| record.revision == revision && !recused(&record.reviewer_id))
+            .map(|record| (&record.reviewer_id, record.decision))
+            .collect();
+        let mut decisions = HashMap::new();
+        for decision in latest.into_values() {
+            *decisions.entry(decision).or_insert(0) += 1;
+        }
+        decisions
+    }
+}
+
+impl Contract {
+    /// Starts a new review round, keeping the log of the previous ones.
+    pub(crate) fn open_review_round(&mut self, id: ProposalId) {
+        let mut reviews = self.proposal_reviews.get(&id).unwrap_or_default();
+        reviews.round_start = reviews.log.len().try_into().unwrap();
+        self.proposal_reviews.insert(&id, &reviews);
+    }
+
+    fn is_reviewer(&self, policy: &ReviewPolicy, account_id: &AccountId) -> bool {
+        policy
+            .reviewer_teams
+            .iter()
+            .any(|team| self.access_control.members_list.is_in_team(account_id.clone(), team))
+    }
+}
+
+#[near]
+impl Contract {
+    /// Records the decision of a reviewer and moves the proposal to the matching status once the
+    /// threshold of the review policy of its category is met. The attached deposit has to cover
+    /// the storage of the review, the rest is refunded.
+    #[payable]
+    pub fn submit_review(
+        &mut self,
+        proposal_id: ProposalId,
+        decision: ReviewDecision,
+        rationale: String,
+    ) -> ProposalId {
+        let proposal = self.migrate_proposal_history(proposal_id);
+        let mut body = proposal.snapshot.body.clone().latest_version();
+        let current_timeline = body.timeline.clone().latest_version();
+        require!(current_timeline.is_review(), "Only proposals in review can be reviewed");
+        let policy = self.review_policies.get(&body.category).unwrap_or_else(|| {
+            panic!("There is no review policy for the category {}", body.category)
+        });
+        let reviewer_id = env::predecessor_account_id();
+        require!(
+            self.is_reviewer(&policy, &reviewer_id),
+            "The account is not a reviewer of this category"
+        );
+        require!(reviewer_id != proposal.author_id, "The author cannot review their own proposal");
+        require!(
+            !self.has_conflict(&proposal, &reviewer_id),
+            "The account has a conflict of interest with this proposal"
+        );
+        require!(!rationale.trim().is_empty(), "The rationale cannot be empty");
+        require!(
+            rationale.chars().count() <= MAX_RATIONALE_LENGTH,
+            format!("The rationale cannot be longer than {} characters", MAX_RATIONALE_LENGTH)
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let mut reviews = self.proposal_reviews.get(&proposal_id).unwrap_or_default();
+        reviews.log.push(ReviewRecord {
+            reviewer_id: reviewer_id.clone(),
+            decision,
+            rationale,
+            timestamp: env::block_timestamp(),
+            revision: proposal.revision,
+        });
+        self.proposal_reviews.insert(&proposal_id, &reviews);
+        charge_storage_deposit(initial_storage_usage);
+
+        // Reviewers who declared a conflict after reviewing no longer count.
+        let count = reviews
+            .current_decisions(proposal.revision, |reviewer| self.has_conflict(&proposal, reviewer))
+            .get(&decision)
+            .copied()
+            .unwrap_or(0);
+        if count >= policy.threshold(decision) {
+            let mut new_timeline = decision.outcome(&current_timeline);
+            let old_body = body.clone();
+            self.settle_timeline_change(
+                proposal_id,
+                &body,
+                &old_body,
+                &mut new_timeline,
+                &reviewer_id,
+            );
+            body.timeline = new_timeline.into();
+            let labels = proposal.snapshot.labels.clone();
+            let authors = self.get_proposal_authors(&proposal);
+            self.save_proposal_snapshot(proposal, body.into(), labels);
+            crate::notify::notify_edit_proposal(proposal_id, authors);
+        }
+        proposal_id
+    }
+
+    pub fn get_proposal_reviews(&self, proposal_id: ProposalId) -> ProposalReviews {
+        self.proposal_reviews.get(&proposal_id).unwrap_or_default()
+    }
+
+    pub fn get_review_policies(&self) -> HashMap<String, ReviewPolicy> {
+        self.review_policies.iter().collect()
+    }
+
+    /// Sets the review policy of the category, `None` leaves the review to the moderators.
+    #[payable]
+    pub fn set_review_policy(&mut self, category: String, policy: Option<ReviewPolicy>) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can set review policies"
+        );
+        self.assert_known_category(&category);
+        match policy {
+            Some(policy) => {
+                require!(!policy.reviewer_teams.is_empty(), "The policy needs reviewer teams");
+                require!(
+                    policy.approve_threshold > 0
+                        && policy.reject_threshold > 0
+                        && policy.approve_conditionally_threshold > 0,
+                    "Review thresholds must be positive"
+                );
+                self.review_policies.insert(&category, &policy);
+            }
+            None => {
+                self.review_policies.remove(&category);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::json;
+    use near_sdk::NearToken;
+
+    use super::ReviewDecision;
+    use crate::access_control::members::{Member, MemberMetadata};
+    use crate::proposal::timeline::TimelineStatus;
+    use crate::test_fixtures::{proposal_body, set_deposit, set_predecessor, store_proposal};
+    use crate::{Contract, Proposal, ProposalV0, VersionedProposal};
+
+    const DEPOSIT: NearToken = NearToken::from_millinear(10);
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        let body = proposal_body(json!({
+            "supervisor": "frol.near",
+            "timeline": {"status": "REVIEW", "sponsor_requested_review": true, "reviewer_completed_attestation": false},
+        }));
+        store_proposal(&mut contract, "bob.near", body);
+
+        let reviewers = ["alice.near", "carol.near", "dave.near"];
+        let members_list = &mut contract.access_control.members_list;
+        for reviewer in reviewers {
+            members_list.add_member(
+                Member::Account(reviewer.parse().unwrap()),
+                MemberMetadata::default().into(),
+            );
+        }
+        members_list.add_member(
+            Member::Team("reviewers".to_string()),
+            MemberMetadata {
+                children: reviewers
+                    .iter()
+                    .map(|reviewer| Member::Account(reviewer.parse().unwrap()))
+                    .collect(),
+                ..Default::default()
+            }
+            .into(),
+        );
+        contract.set_review_policy(
+            "Marketing".to_string(),
+            Some(super::ReviewPolicy {
+                reviewer_teams: HashSet::from(["reviewers".to_string()]),
+                approve_threshold: 2,
+                reject_threshold: 2,
+                approve_conditionally_threshold: 2,
+            }),
+        );
+        contract
+    }
+
+    fn timeline(contract: &Contract) -> TimelineStatus {
+        let proposal: Proposal = contract.get_proposal(0).into();
+        proposal.snapshot.body.latest_version().timeline.latest_version()
+    }
+
+    #[test]
+    fn moves_proposal_once_threshold_is_met() {
+        let mut contract = setup();
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Reject, "Too expensive".to_string());
+        contract.submit_review(0, ReviewDecision::Approve, "Budget clarified".to_string());
+        set_deposit("carol.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Reject, "Out of scope".to_string());
+        assert!(timeline(&contract).is_review());
+
+        set_deposit("dave.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Looks good".to_string());
+        assert!(matches!(timeline(&contract), TimelineStatus::Approved(..)));
+
+        let reviews = contract.get_proposal_reviews(0);
+        assert_eq!(reviews.log.len(), 4);
+        assert_eq!(reviews.log[0].rationale, "Too expensive");
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.revision, 1);
+        assert_eq!(proposal.snapshot.editor_id.as_str(), "dave.near");
+    }
+
+    #[test]
+    fn skips_reviews_of_recused_reviewers() {
+        let mut contract = setup();
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Looks good".to_string());
+        contract.declare_conflict(0, "I joined the team".to_string());
+        set_deposit("carol.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Agreed".to_string());
+        assert!(timeline(&contract).is_review());
+
+        set_deposit("dave.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Fine".to_string());
+        assert!(matches!(timeline(&contract), TimelineStatus::Approved(..)));
+    }
+
+    #[test]
+    fn drops_reviews_of_previous_revisions() {
+        let mut contract = setup();
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Looks good".to_string());
+        set_predecessor("devhub.near");
+        let proposal: Proposal = contract.get_proposal(0).into();
+        let mut body = proposal.snapshot.body.latest_version();
+        body.description = "Updated description".to_string();
+        contract.edit_proposal(0, body.into(), Default::default());
+
+        set_deposit("carol.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Agreed".to_string());
+        assert!(timeline(&contract).is_review());
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Still good".to_string());
+        assert!(matches!(timeline(&contract), TimelineStatus::Approved(..)));
+    }
+
+    #[test]
+    fn keeps_history_of_legacy_proposals() {
+        let mut contract = setup();
+        let proposal: Proposal = contract.get_proposal(0).into();
+        let mut first_snapshot = proposal.snapshot.clone();
+        first_snapshot.timestamp = 1;
+        let legacy = ProposalV0 {
+            id: 0,
+            author_id: proposal.author_id,
+            social_db_post_block_height: 0,
+            snapshot: proposal.snapshot,
+            snapshot_history: vec![first_snapshot],
+        };
+        contract.proposals.replace(0, &VersionedProposal::V0(legacy));
+
+        set_deposit("alice.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Looks good".to_string());
+        set_deposit("carol.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Agreed".to_string());
+        assert!(matches!(timeline(&contract), TimelineStatus::Approved(..)));
+
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.revision, 2);
+        assert_eq!(contract.get_proposal_snapshot(0, 0).unwrap().timestamp, 1);
+        assert_eq!(contract.get_proposal_snapshot(0, 1).unwrap().timestamp, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account is not a reviewer of this category")]
+    fn only_reviewers_review() {
+        let mut contract = setup();
+        set_deposit("eve.near", DEPOSIT);
+        contract.submit_review(0, ReviewDecision::Approve, "Approve".to_string());
+    }
+}
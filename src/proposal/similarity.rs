@@ -0,0 +1,412 @@
+use std::collections::{BTreeSet, HashMap};
+
+use near_sdk::json_types::U64;
+use near_sdk::{env, near, require, AccountId, CryptoHash, Gas, Timestamp};
+
+use super::query::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use super::{Proposal, ProposalBodyV3, ProposalId};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+/// Number of consecutive words of a shingle.
+const SHINGLE_SIZE: usize = 3;
+/// Number of shingles kept per proposal. The smallest hashes are kept, so that two texts sharing
+/// many shingles also share many of the kept ones.
+const SKETCH_SIZE: usize = 32;
+/// Shingles shared by more proposals are too common to tell proposals apart and are not indexed
+/// any further.
+const MAX_SHINGLE_PROPOSALS: usize = 500;
+/// Gas left untouched by a batch of `backfill_proposal_fingerprints` so that it can record its
+/// progress.
+const BACKFILL_GAS_RESERVE: Gas = Gas::from_tgas(20);
+
+/// Normalized fingerprint and shingle sketch of the text of a proposal.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalFingerprint {
+    pub author_id: AccountId,
+    pub hash: CryptoHash,
+    pub shingles: Vec<u64>,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub created_at: Timestamp,
+}
+
+/// Position of `backfill_proposal_fingerprints` among the proposals created before fingerprints.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default)]
+pub struct FingerprintBackfill {
+    pub next_proposal: ProposalId,
+    pub done: bool,
+}
+
+#[near(serializers=[json])]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityQuery {
+    Text(String),
+    ProposalId(ProposalId),
+}
+
+#[near(serializers=[json])]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimilarProposal {
+    pub proposal_id: ProposalId,
+    /// Number of sketch shingles shared with the query.
+    pub shared_shingles: u32,
+    /// Share of the shingles of the larger sketch which are shared, in percent.
+    pub score: u32,
+}
+
+/// Lowercase alphanumeric words of the text.
+fn normalized_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+pub(crate) fn proposal_text(body: &ProposalBodyV3) -> String {
+    format!("{}\n{}\n{}", body.name, body.summary, body.description)
+}
+
+/// 64-bit FNV-1a, cheap enough to hash every shingle of a long description.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+pub fn shingle_sketch(text: &str) -> Vec<u64> {
+    let words = normalized_words(text);
+    let sketch: BTreeSet<u64> = words
+        .windows(SHINGLE_SIZE.min(words.len()).max(1))
+        .map(|shingle| fnv1a(shingle.join(" ").as_bytes()))
+        .collect();
+    sketch.into_iter().take(SKETCH_SIZE).collect()
+}
+
+pub fn text_fingerprint(text: &str) -> CryptoHash {
+    env::sha256_array(normalized_words(text).join(" ").as_bytes())
+}
+
+impl Contract {
+    /// Indexes the text of the proposal, replacing the entries of its previous text.
+    pub(crate) fn index_proposal_fingerprint(&mut self, proposal: &Proposal) {
+        self.index_proposal_fingerprint_at(proposal, env::block_timestamp());
+    }
+
+    /// Like `index_proposal_fingerprint`, with `created_at` used when the proposal has no
+    /// fingerprint yet.
+    fn index_proposal_fingerprint_at(&mut self, proposal: &Proposal, created_at: Timestamp) {
+        let id = proposal.id;
+        let text = proposal_text(&proposal.snapshot.body.clone().latest_version());
+        let created_at = match self.proposal_fingerprints.get(&id) {
+            Some(previous) => {
+                self.unindex_proposal_fingerprint(id, &previous);
+                previous.created_at
+            }
+            None => created_at,
+        };
+        let fingerprint = ProposalFingerprint {
+            author_id: proposal.author_id.clone(),
+            hash: text_fingerprint(&text),
+            shingles: shingle_sketch(&text),
+            created_at,
+        };
+
+        let mut same_text = self.fingerprint_proposals.get(&fingerprint.hash).unwrap_or_default();
+        same_text.push(id);
+        self.fingerprint_proposals.insert(&fingerprint.hash, &same_text);
+        for shingle in &fingerprint.shingles {
+            let mut proposals = self.shingle_proposals.get(shingle).unwrap_or_default();
+            if proposals.len() < MAX_SHINGLE_PROPOSALS {
+                proposals.push(id);
+                self.shingle_proposals.insert(shingle, &proposals);
+            }
+        }
+        self.proposal_fingerprints.insert(&id, &fingerprint);
+    }
+
+    fn unindex_proposal_fingerprint(&mut self, id: ProposalId, fingerprint: &ProposalFingerprint) {
+        let mut same_text = self.fingerprint_proposals.get(&fingerprint.hash).unwrap_or_default();
+        same_text.retain(|other| *other != id);
+        self.fingerprint_proposals.insert(&fingerprint.hash, &same_text);
+        for shingle in &fingerprint.shingles {
+            let mut proposals = self.shingle_proposals.get(shingle).unwrap_or_default();
+            proposals.retain(|other| *other != id);
+            self.shingle_proposals.insert(shingle, &proposals);
+        }
+    }
+
+    /// Keeps the author of the fingerprint in line with the author of the proposal, so that the
+    /// duplicate check applies to the new author.
+    pub(crate) fn update_fingerprint_author(&mut self, id: ProposalId, author_id: &AccountId) {
+        if let Some(mut fingerprint) = self.proposal_fingerprints.get(&id) {
+            fingerprint.author_id = author_id.clone();
+            self.proposal_fingerprints.insert(&id, &fingerprint);
+        }
+    }
+
+    /// In strict mode, rejects a proposal whose text matches the one of a proposal submitted by
+    /// the same author within the duplicate window.
+    pub(crate) fn assert_not_duplicate(&self, body: &ProposalBodyV3) {
+        let Some(window) = self.duplicate_window else {
+            return;
+        };
+        let author_id = env::predecessor_account_id();
+        let now = env::block_timestamp();
+        let hash = text_fingerprint(&proposal_text(body));
+        for other in self.fingerprint_proposals.get(&hash).unwrap_or_default() {
+            let Some(fingerprint) = self.proposal_fingerprints.get(&other) else {
+                continue;
+            };
+            let same_author = fingerprint.author_id == author_id;
+            require!(
+                !same_author || now.saturating_sub(fingerprint.created_at) >= window,
+                format!("The proposal duplicates proposal {} submitted by the author", other)
+            );
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    /// Candidates sharing the most shingles with the text or with the text of the proposal.
+    pub fn find_similar_proposals(
+        &self,
+        query: SimilarityQuery,
+        limit: Option<u32>,
+    ) -> Vec<SimilarProposal> {
+        let (sketch, exclude) = match query {
+            SimilarityQuery::Text(text) => (shingle_sketch(&text), None),
+            SimilarityQuery::ProposalId(id) => {
+                let proposal: Proposal = self.get_proposal(id).into();
+                let sketch = self
+                    .proposal_fingerprints
+                    .get(&id)
+                    .map(|fingerprint| fingerprint.shingles)
+                    .unwrap_or_else(|| {
+                        shingle_sketch(&proposal_text(&proposal.snapshot.body.latest_version()))
+                    });
+                (sketch, Some(id))
+            }
+        };
+
+        let mut shared: HashMap<ProposalId, u32> = HashMap::new();
+        for shingle in &sketch {
+            for id in self.shingle_proposals.get(shingle).unwrap_or_default() {
+                if Some(id) != exclude {
+                    *shared.entry(id).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut candidates: Vec<SimilarProposal> = shared
+            .into_iter()
+            .map(|(proposal_id, shared_shingles)| {
+                let other_size = self
+                    .proposal_fingerprints
+                    .get(&proposal_id)
+                    .map_or(0, |fingerprint| fingerprint.shingles.len());
+                let size = sketch.len().max(other_size).max(1) as u32;
+                SimilarProposal {
+                    proposal_id,
+                    shared_shingles,
+                    score: shared_shingles * 100 / size,
+                }
+            })
+            .collect();
+        candidates.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.shared_shingles.cmp(&a.shared_shingles))
+                .then(a.proposal_id.cmp(&b.proposal_id))
+        });
+        candidates.truncate(limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize);
+        candidates
+    }
+
+    /// Fingerprints up to `limit` of the proposals created before fingerprints and returns the
+    /// progress. Call it again until `done` is set.
+    pub fn backfill_proposal_fingerprints(&mut self, limit: Option<u32>) -> FingerprintBackfill {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can backfill the proposal fingerprints"
+        );
+        let mut progress = self.fingerprint_backfill.clone();
+        let proposals_count: ProposalId = self.proposals.len().try_into().unwrap();
+        let mut remaining = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        while remaining > 0
+            && progress.next_proposal < proposals_count
+            && env::prepaid_gas().saturating_sub(env::used_gas()) > BACKFILL_GAS_RESERVE
+        {
+            let id = progress.next_proposal;
+            if self.proposal_fingerprints.get(&id).is_none() {
+                let proposal: Proposal = self.proposals.get(id.into()).unwrap().into();
+                // The duplicate window counts from the first snapshot of the proposal.
+                let created_at = self
+                    .get_proposal_snapshot(id, 0)
+                    .map_or(proposal.snapshot.timestamp, |snapshot| snapshot.timestamp);
+                self.index_proposal_fingerprint_at(&proposal, created_at);
+            }
+            progress.next_proposal += 1;
+            remaining -= 1;
+        }
+        progress.done = progress.next_proposal == proposals_count;
+        self.fingerprint_backfill = progress.clone();
+        progress
+    }
+
+    pub fn get_duplicate_window(&self) -> Option<U64> {
+        self.duplicate_window.map(U64)
+    }
+
+    /// Enables the strict mode rejecting exact duplicates from the same author submitted within
+    /// `window` nanoseconds, or disables it with `None`.
+    #[payable]
+    pub fn set_duplicate_window(&mut self, window: Option<U64>) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can set the duplicate window"
+        );
+        self.duplicate_window = window.map(u64::from);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::json_types::U64;
+    use near_sdk::serde_json::json;
+    use near_sdk::NearToken;
+
+    use super::{shingle_sketch, text_fingerprint, SimilarityQuery};
+    use crate::test_fixtures::{proposal_body, set_context, store_proposal};
+    use crate::{Contract, VersionedProposalBody};
+
+    #[test]
+    fn normalizes_texts() {
+        assert_eq!(text_fingerprint("Hello, World!"), text_fingerprint("hello   world"));
+        assert_eq!(shingle_sketch("One two three four").len(), 2);
+        assert_eq!(shingle_sketch("Short").len(), 1);
+    }
+
+    /// Stores the proposal like `set_block_height_callback` does.
+    fn push_proposal(contract: &mut Contract, author: &str, body: VersionedProposalBody) {
+        let proposal = store_proposal(contract, author, body);
+        contract.index_proposal_fingerprint(&proposal);
+    }
+
+    #[test]
+    fn finds_similar_proposals() {
+        set_context("bob.near", 0, NearToken::from_yoctonear(0));
+        let mut contract = Contract::new();
+        push_proposal(
+            &mut contract,
+            "bob.near",
+            proposal_body(json!({
+                "name": "Indexer",
+                "description": "We will build an indexer for the NEAR blockchain with a GraphQL API",
+            })),
+        );
+        push_proposal(
+            &mut contract,
+            "alice.near",
+            proposal_body(json!({
+                "name": "Wallet",
+                "description": "A mobile wallet with social recovery and hardware key support",
+            })),
+        );
+
+        let similar = contract.find_similar_proposals(
+            SimilarityQuery::Text(
+                "Indexer: we will build an indexer for the NEAR blockchain with a REST API".into(),
+            ),
+            None,
+        );
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].proposal_id, 0);
+        assert!(similar[0].score >= 50);
+        assert!(contract.find_similar_proposals(SimilarityQuery::ProposalId(0), None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal duplicates proposal 0 submitted by the author")]
+    fn rejects_duplicates_in_strict_mode() {
+        set_context("devhub.near", 0, NearToken::from_yoctonear(0));
+        let mut contract = Contract::new();
+        contract.set_duplicate_window(Some(U64(100)));
+        push_proposal(
+            &mut contract,
+            "bob.near",
+            proposal_body(json!({"name": "Indexer", "description": "Description"})),
+        );
+
+        set_context("alice.near", 20, NearToken::from_yoctonear(0));
+        contract.add_proposal(
+            proposal_body(json!({"name": "Indexer", "description": "Description"})),
+            HashSet::new(),
+            Some(0),
+        );
+        set_context("bob.near", 50, NearToken::from_yoctonear(0));
+        contract.add_proposal(
+            proposal_body(json!({"name": "indexer", "description": "description."})),
+            HashSet::new(),
+            Some(0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal duplicates proposal 0 submitted by the author")]
+    fn rejects_duplicates_of_transferred_proposals() {
+        set_context("devhub.near", 0, NearToken::from_yoctonear(0));
+        let mut contract = Contract::new();
+        contract.set_duplicate_window(Some(U64(100)));
+        push_proposal(
+            &mut contract,
+            "bob.near",
+            proposal_body(json!({"name": "Indexer", "description": "Description"})),
+        );
+        contract.force_proposal_transfer(0, "alice.near".parse().unwrap());
+
+        set_context("alice.near", 20, NearToken::from_yoctonear(0));
+        contract.add_proposal(
+            proposal_body(json!({"name": "Indexer", "description": "Description"})),
+            HashSet::new(),
+            Some(0),
+        );
+    }
+
+    #[test]
+    fn backfills_fingerprints() {
+        set_context("devhub.near", 0, NearToken::from_yoctonear(0));
+        let mut contract = Contract::new();
+        store_proposal(
+            &mut contract,
+            "bob.near",
+            proposal_body(json!({"name": "Indexer", "description": "An indexer API"})),
+        );
+        store_proposal(
+            &mut contract,
+            "alice.near",
+            proposal_body(json!({"name": "Wallet", "description": "A mobile wallet"})),
+        );
+
+        let progress = contract.backfill_proposal_fingerprints(Some(1));
+        assert!(!progress.done);
+        assert_eq!(progress.next_proposal, 1);
+        assert!(contract.backfill_proposal_fingerprints(None).done);
+
+        let similar = contract
+            .find_similar_proposals(SimilarityQuery::Text("A mobile wallet".to_string()), None);
+        assert_eq!(similar[0].proposal_id, 1);
+        assert_eq!(
+            contract.proposal_fingerprints.get(&1).unwrap().author_id.as_str(),
+            "alice.near"
+        );
+    }
+}
@@ -0,0 +1,573 @@
+pub mod transitions;
+
+use std::fmt;
+
+use near_sdk::{near, AccountId, Timestamp};
+
+use super::ProposalFundingCurrency;
+use crate::str_serializers::*;
+
+pub type TimelineStatus = TimelineStatusV4;
+type ReviewStatus = ReviewStatusV3;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusV1 {
+    Draft,
+    Review(ReviewStatusV1),
+    Approved(ReviewStatusV1),
+    Rejected(ReviewStatusV1),
+    ApprovedConditionally(ReviewStatusV1),
+    PaymentProcessing(PaymentProcessingStatusV1),
+    Funded(FundedStatusV1),
+    Cancelled(ReviewStatusV1),
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "timeline_version")]
+pub enum VersionedTimelineStatus {
+    V1(TimelineStatusV2),
+    V2(TimelineStatusV3),
+    V3(TimelineStatusV4),
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusV2 {
+    Draft,
+    Review(ReviewStatusV2),
+    Approved(ReviewStatusV2),
+    Rejected(ReviewStatusV2),
+    ApprovedConditionally(ReviewStatusV2),
+    PaymentProcessing(PaymentProcessingStatusV2),
+    Funded(FundedStatusV2),
+    Cancelled(ReviewStatusV2),
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusV3 {
+    Draft,
+    Review(ReviewStatusV3),
+    Approved(ReviewStatusV3),
+    Rejected(ReviewStatusV3),
+    ApprovedConditionally(ReviewStatusV3),
+    PaymentProcessing(PaymentProcessingStatusV3),
+    Funded(FundedStatusV3),
+    Cancelled(ReviewStatusV3),
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusV4 {
+    Draft,
+    Review(ReviewStatusV3),
+    Approved(ReviewStatusV3),
+    Rejected(ReviewStatusV3),
+    ApprovedConditionally(ReviewStatusV3),
+    PaymentProcessing(PaymentProcessingStatusV3),
+    Funded(FundedStatusV4),
+    Cancelled(ReviewStatusV3),
+}
+
+fn convert_review_status_to_v1(review_status: ReviewStatusV1, kyc_verified: bool) -> ReviewStatusV2 {
+    ReviewStatusV2 {
+        sponsor_requested_review: review_status.sponsor_requested_review,
+        reviewer_completed_attestation: review_status.reviewer_completed_attestation,
+        kyc_verified: kyc_verified,
+    }
+}
+
+impl From<PaymentProcessingStatusV1> for PaymentProcessingStatusV2 {
+    fn from(value: PaymentProcessingStatusV1) -> Self {
+        PaymentProcessingStatusV2 {
+            review_status: convert_review_status_to_v1(value.review_status, value.kyc_verified),
+            kyc_verified_deprecated: false,
+            test_transaction_sent: value.test_transaction_sent,
+            request_for_trustees_created: value.request_for_trustees_created,
+        }
+    }
+}
+
+impl From<FundedStatusV1> for FundedStatusV2 {
+    fn from(value: FundedStatusV1) -> Self {
+        FundedStatusV2 {
+            payment_processing_status: value.payment_processing_status.into(),
+            trustees_released_payment: value.trustees_released_payment,
+            payouts: value.payouts,
+        }
+    }
+}
+
+impl From<TimelineStatusV1> for TimelineStatusV2 {
+    fn from(value: TimelineStatusV1) -> Self {
+        match value {
+            TimelineStatusV1::Draft => TimelineStatusV2::Draft,
+            TimelineStatusV1::Review(review_status) => TimelineStatusV2::Review(convert_review_status_to_v1(review_status, false)),
+            TimelineStatusV1::Approved(review_status) => TimelineStatusV2::Approved(convert_review_status_to_v1(review_status, false)),
+            TimelineStatusV1::Rejected(review_status) => TimelineStatusV2::Rejected(convert_review_status_to_v1(review_status, false)),
+            TimelineStatusV1::ApprovedConditionally(review_status) => {
+                TimelineStatusV2::ApprovedConditionally(convert_review_status_to_v1(review_status, false))
+            }
+            TimelineStatusV1::PaymentProcessing(payment_processing_status) => {
+                TimelineStatusV2::PaymentProcessing(payment_processing_status.into())
+            }
+            TimelineStatusV1::Funded(funded_status) => TimelineStatusV2::Funded(funded_status.into()),
+            TimelineStatusV1::Cancelled(review_status) => TimelineStatusV2::Cancelled(convert_review_status_to_v1(review_status, false)),
+        }
+    }
+}
+
+impl From<ReviewStatusV2> for ReviewStatusV3 {
+    fn from(value: ReviewStatusV2) -> Self {
+        ReviewStatusV3 {
+            sponsor_requested_review: value.sponsor_requested_review,
+            reviewer_completed_attestation: value.reviewer_completed_attestation,
+            kyc_verified: value.kyc_verified,
+            review_requested_by: None,
+            attested_by: None,
+        }
+    }
+}
+
+impl From<PaymentProcessingStatusV2> for PaymentProcessingStatusV3 {
+    fn from(value: PaymentProcessingStatusV2) -> Self {
+        PaymentProcessingStatusV3 {
+            review_status: value.review_status.into(),
+            kyc_verified_deprecated: value.kyc_verified_deprecated,
+            test_transaction_sent: value.test_transaction_sent,
+            request_for_trustees_created: value.request_for_trustees_created,
+        }
+    }
+}
+
+impl From<FundedStatusV2> for FundedStatusV3 {
+    fn from(value: FundedStatusV2) -> Self {
+        FundedStatusV3 {
+            payment_processing_status: value.payment_processing_status.into(),
+            trustees_released_payment: value.trustees_released_payment,
+            payouts: value.payouts,
+        }
+    }
+}
+
+impl From<TimelineStatusV2> for TimelineStatusV3 {
+    fn from(value: TimelineStatusV2) -> Self {
+        match value {
+            TimelineStatusV2::Draft => TimelineStatusV3::Draft,
+            TimelineStatusV2::Review(review_status) => TimelineStatusV3::Review(review_status.into()),
+            TimelineStatusV2::Approved(review_status) => {
+                TimelineStatusV3::Approved(review_status.into())
+            }
+            TimelineStatusV2::Rejected(review_status) => {
+                TimelineStatusV3::Rejected(review_status.into())
+            }
+            TimelineStatusV2::ApprovedConditionally(review_status) => {
+                TimelineStatusV3::ApprovedConditionally(review_status.into())
+            }
+            TimelineStatusV2::PaymentProcessing(payment_processing_status) => {
+                TimelineStatusV3::PaymentProcessing(payment_processing_status.into())
+            }
+            TimelineStatusV2::Funded(funded_status) => TimelineStatusV3::Funded(funded_status.into()),
+            TimelineStatusV2::Cancelled(review_status) => {
+                TimelineStatusV3::Cancelled(review_status.into())
+            }
+        }
+    }
+}
+
+impl From<FundedStatusV3> for FundedStatusV4 {
+    fn from(value: FundedStatusV3) -> Self {
+        FundedStatusV4 {
+            payment_processing_status: value.payment_processing_status,
+            trustees_released_payment: value.trustees_released_payment,
+            payouts: vec![],
+            legacy_payouts: value.payouts,
+        }
+    }
+}
+
+impl From<TimelineStatusV3> for TimelineStatusV4 {
+    fn from(value: TimelineStatusV3) -> Self {
+        match value {
+            TimelineStatusV3::Draft => TimelineStatusV4::Draft,
+            TimelineStatusV3::Review(review_status) => TimelineStatusV4::Review(review_status),
+            TimelineStatusV3::Approved(review_status) => TimelineStatusV4::Approved(review_status),
+            TimelineStatusV3::Rejected(review_status) => TimelineStatusV4::Rejected(review_status),
+            TimelineStatusV3::ApprovedConditionally(review_status) => {
+                TimelineStatusV4::ApprovedConditionally(review_status)
+            }
+            TimelineStatusV3::PaymentProcessing(payment_processing_status) => {
+                TimelineStatusV4::PaymentProcessing(payment_processing_status)
+            }
+            TimelineStatusV3::Funded(funded_status) => TimelineStatusV4::Funded(funded_status.into()),
+            TimelineStatusV3::Cancelled(review_status) => TimelineStatusV4::Cancelled(review_status),
+        }
+    }
+}
+
+/// Status of a proposal timeline without the attached review and payment details.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusKind {
+    Draft,
+    Review,
+    Approved,
+    Rejected,
+    ApprovedConditionally,
+    PaymentProcessing,
+    Funded,
+    Cancelled,
+}
+
+impl fmt::Display for TimelineStatusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = near_sdk::serde_json::to_value(self).map_err(|_| fmt::Error)?;
+        f.write_str(value.as_str().unwrap_or_default())
+    }
+}
+
+impl VersionedTimelineStatus {
+    pub fn latest_version(self) -> TimelineStatus {
+        self.into()
+    }
+}
+
+impl From<VersionedTimelineStatus> for TimelineStatusV4 {
+    fn from(value: VersionedTimelineStatus) -> Self {
+        match value {
+            VersionedTimelineStatus::V1(v1) => TimelineStatusV3::from(v1).into(),
+            VersionedTimelineStatus::V2(v2) => v2.into(),
+            VersionedTimelineStatus::V3(v3) => v3,
+        }
+    }
+}
+
+impl From<TimelineStatusV2> for VersionedTimelineStatus {
+    fn from(value: TimelineStatusV2) -> Self {
+        VersionedTimelineStatus::V1(value)
+    }
+}
+
+impl From<TimelineStatusV3> for VersionedTimelineStatus {
+    fn from(value: TimelineStatusV3) -> Self {
+        VersionedTimelineStatus::V2(value)
+    }
+}
+
+impl From<TimelineStatusV4> for VersionedTimelineStatus {
+    fn from(value: TimelineStatusV4) -> Self {
+        VersionedTimelineStatus::V3(value)
+    }
+}
+
+impl From<TimelineStatusV1> for VersionedTimelineStatus {
+    fn from(value: TimelineStatusV1) -> Self {
+        let v3: TimelineStatusV3 = TimelineStatusV2::from(value).into();
+        VersionedTimelineStatus::V3(v3.into())
+    }
+}
+
+impl TimelineStatus {
+    pub fn kind(&self) -> TimelineStatusKind {
+        match self {
+            TimelineStatus::Draft => TimelineStatusKind::Draft,
+            TimelineStatus::Review(..) => TimelineStatusKind::Review,
+            TimelineStatus::Approved(..) => TimelineStatusKind::Approved,
+            TimelineStatus::Rejected(..) => TimelineStatusKind::Rejected,
+            TimelineStatus::ApprovedConditionally(..) => TimelineStatusKind::ApprovedConditionally,
+            TimelineStatus::PaymentProcessing(..) => TimelineStatusKind::PaymentProcessing,
+            TimelineStatus::Funded(..) => TimelineStatusKind::Funded,
+            TimelineStatus::Cancelled(..) => TimelineStatusKind::Cancelled,
+        }
+    }
+
+    pub fn is_draft(&self) -> bool {
+        matches!(self, TimelineStatus::Draft)
+    }
+
+    pub fn is_empty_review(&self) -> bool {
+        match self {
+            TimelineStatus::Review(review_status) => {
+                !review_status.sponsor_requested_review
+                    && !review_status.reviewer_completed_attestation
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_review(&self) -> bool {
+        matches!(self, TimelineStatus::Review(..))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, TimelineStatus::Cancelled(..))
+    }
+
+    pub fn can_be_cancelled(&self) -> bool {
+        match self {
+            TimelineStatus::Draft => true,
+            TimelineStatus::Review(..) => true,
+            _ => false,
+        }
+    }
+
+    pub fn was_approved(&self) -> bool {
+        match self {
+            TimelineStatus::Approved(..) => true,
+            TimelineStatus::ApprovedConditionally(..) => true,
+            TimelineStatus::PaymentProcessing(..) => true,
+            TimelineStatus::Funded(..) => true,
+            _ => false,
+            
+        }
+    }
+
+    pub fn get_review_status(&self) -> &ReviewStatus {
+        match self {
+            TimelineStatus::Review(review_status)
+            | TimelineStatus::Approved(review_status)
+            | TimelineStatus::Rejected(review_status)
+            | TimelineStatus::ApprovedConditionally(review_status)
+            | TimelineStatus::Cancelled(review_status) => review_status.into(),
+            TimelineStatus::PaymentProcessing(payment_processing_status) => {
+                &payment_processing_status.review_status
+            },
+            TimelineStatus::Funded(funded_status) => {
+                &funded_status.payment_processing_status.review_status
+            },
+            TimelineStatus::Draft => &ReviewStatus {
+                sponsor_requested_review: false,
+                reviewer_completed_attestation: false,
+                kyc_verified: false,
+                review_requested_by: None,
+                attested_by: None,
+            },
+        }
+    }
+
+    /// Payouts made so far, empty unless the proposal is funded.
+    pub fn get_payouts(&self) -> &[Payout] {
+        match self {
+            TimelineStatus::Funded(funded_status) => &funded_status.payouts,
+            _ => &[],
+        }
+    }
+
+    pub fn get_review_status_mut(&mut self) -> Option<&mut ReviewStatus> {
+        match self {
+            TimelineStatus::Review(review_status)
+            | TimelineStatus::Approved(review_status)
+            | TimelineStatus::Rejected(review_status)
+            | TimelineStatus::ApprovedConditionally(review_status)
+            | TimelineStatus::Cancelled(review_status) => Some(review_status),
+            TimelineStatus::PaymentProcessing(payment_processing_status) => {
+                Some(&mut payment_processing_status.review_status)
+            }
+            TimelineStatus::Funded(funded_status) => {
+                Some(&mut funded_status.payment_processing_status.review_status)
+            }
+            TimelineStatus::Draft => None,
+        }
+    }
+
+    /// Makes sure the sign-off records of the review checklist are only set by the account which
+    /// sets the flag. A record naming `editor_id` and `timestamp` is accepted, otherwise the record
+    /// from `previous` is kept while its flag stays set.
+    pub fn settle_sign_offs(
+        &mut self,
+        previous: &TimelineStatus,
+        editor_id: &AccountId,
+        timestamp: Timestamp,
+    ) {
+        let previous = previous.get_review_status();
+        if let Some(review_status) = self.get_review_status_mut() {
+            let current = ReviewSignOff { account_id: editor_id.clone(), timestamp };
+            review_status.review_requested_by = settle_sign_off(
+                review_status.sponsor_requested_review,
+                review_status.review_requested_by.take(),
+                previous.sponsor_requested_review,
+                &previous.review_requested_by,
+                &current,
+            );
+            review_status.attested_by = settle_sign_off(
+                review_status.reviewer_completed_attestation,
+                review_status.attested_by.take(),
+                previous.reviewer_completed_attestation,
+                &previous.attested_by,
+                &current,
+            );
+        }
+    }
+}
+
+fn settle_sign_off(
+    flag: bool,
+    sign_off: Option<ReviewSignOff>,
+    previous_flag: bool,
+    previous_sign_off: &Option<ReviewSignOff>,
+    current: &ReviewSignOff,
+) -> Option<ReviewSignOff> {
+    if !flag {
+        None
+    } else if sign_off.as_ref() == Some(current) {
+        sign_off
+    } else if previous_flag {
+        previous_sign_off.clone()
+    } else {
+        None
+    }
+}
+
+impl ReviewStatus {
+    pub fn request_review(&mut self, account_id: AccountId, timestamp: Timestamp) {
+        self.sponsor_requested_review = true;
+        self.review_requested_by = Some(ReviewSignOff { account_id, timestamp });
+    }
+
+    pub fn attest(&mut self, account_id: AccountId, timestamp: Timestamp) {
+        self.reviewer_completed_attestation = true;
+        self.attested_by = Some(ReviewSignOff { account_id, timestamp });
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ReviewStatusV1 {
+    sponsor_requested_review: bool,
+    reviewer_completed_attestation: bool,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ReviewStatusV2 {
+    sponsor_requested_review: bool,
+    reviewer_completed_attestation: bool,
+    kyc_verified: bool,
+}
+
+/// Who ticked an item of the review checklist and when.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewSignOff {
+    pub account_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ReviewStatusV3 {
+    sponsor_requested_review: bool,
+    reviewer_completed_attestation: bool,
+    kyc_verified: bool,
+    /// Set by the requested sponsor through `request_review`.
+    #[serde(default)]
+    review_requested_by: Option<ReviewSignOff>,
+    /// Set by the supervisor through `attest_review`.
+    #[serde(default)]
+    attested_by: Option<ReviewSignOff>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct PaymentProcessingStatusV1 {
+    #[serde(flatten)]
+    review_status: ReviewStatusV1,
+    kyc_verified: bool,
+    test_transaction_sent: bool,
+    request_for_trustees_created: bool,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct PaymentProcessingStatusV2 {
+    #[serde(flatten)]
+    review_status: ReviewStatusV2,
+    #[serde(default)]
+    kyc_verified_deprecated: bool,
+    test_transaction_sent: bool,
+    request_for_trustees_created: bool,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct FundedStatusV1 {
+    #[serde(flatten)]
+    payment_processing_status: PaymentProcessingStatusV1,
+    trustees_released_payment: bool,
+    payouts: Vec<String>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct FundedStatusV2 {
+    #[serde(flatten)]
+    payment_processing_status: PaymentProcessingStatusV2,
+    trustees_released_payment: bool,
+    payouts: Vec<String>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct PaymentProcessingStatusV3 {
+    #[serde(flatten)]
+    review_status: ReviewStatusV3,
+    #[serde(default)]
+    kyc_verified_deprecated: bool,
+    test_transaction_sent: bool,
+    request_for_trustees_created: bool,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct FundedStatusV3 {
+    #[serde(flatten)]
+    payment_processing_status: PaymentProcessingStatusV3,
+    trustees_released_payment: bool,
+    payouts: Vec<String>,
+}
+
+/// A single payment made to fund a proposal.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct Payout {
+    /// Amount in whole units of `currency`. It is not converted to USD, so it is not comparable
+    /// with `requested_sponsorship_usd_amount` or the amounts of the milestones.
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub amount: u32,
+    pub currency: ProposalFundingCurrency,
+    pub receiver: AccountId,
+    pub transaction_hash: String,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    /// Index of the milestone of the proposal the payout is for.
+    pub milestone: Option<u32>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct FundedStatusV4 {
+    #[serde(flatten)]
+    payment_processing_status: PaymentProcessingStatusV3,
+    trustees_released_payment: bool,
+    payouts: Vec<Payout>,
+    /// Links to the payout transactions recorded before payouts were structured.
+    #[serde(default)]
+    legacy_payouts: Vec<String>,
+}
@@ -0,0 +1,150 @@
+//! Allowed moves between the statuses of a proposal timeline and the roles which may make them.
+
+use near_sdk::{near, require};
+
+use super::{TimelineStatus, TimelineStatusKind};
+use TimelineRole::*;
+use TimelineStatusKind::*;
+
+/// Relation of an account to a proposal. An account can hold several roles at once.
+#[near(serializers=[json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineRole {
+    /// The author of the proposal or any other account allowed to edit it.
+    Author,
+    Supervisor,
+    RequestedSponsor,
+    Moderator,
+    /// The contract account itself, which may make any transition.
+    Contract,
+}
+
+/// Edges of the proposal lifecycle. Keeping the status, e.g. `REVIEW` to `REVIEW`, is an edge too
+/// since it is how the review and payment details of the status are updated.
+const TRANSITIONS: &[(TimelineStatusKind, TimelineStatusKind, &[TimelineRole])] = &[
+    (Draft, Draft, &[Author, Moderator]),
+    (Draft, Review, &[Author, Moderator]),
+    (Draft, Cancelled, &[Author, Moderator]),
+    (Review, Review, &[Supervisor, RequestedSponsor, Moderator]),
+    (Review, Draft, &[Moderator]),
+    (Review, Approved, &[Moderator]),
+    (Review, ApprovedConditionally, &[Moderator]),
+    (Review, Rejected, &[Moderator]),
+    (Review, Cancelled, &[Author, Moderator]),
+    (Approved, Approved, &[Moderator]),
+    (Approved, PaymentProcessing, &[Moderator]),
+    (Approved, Cancelled, &[Moderator]),
+    (ApprovedConditionally, ApprovedConditionally, &[Moderator]),
+    (ApprovedConditionally, Approved, &[Moderator]),
+    (ApprovedConditionally, Rejected, &[Moderator]),
+    (ApprovedConditionally, PaymentProcessing, &[Moderator]),
+    (ApprovedConditionally, Cancelled, &[Moderator]),
+    (Rejected, Rejected, &[Moderator]),
+    (Rejected, Review, &[Moderator]),
+    (PaymentProcessing, PaymentProcessing, &[Moderator]),
+    (PaymentProcessing, Funded, &[Moderator]),
+    (PaymentProcessing, Cancelled, &[Moderator]),
+    (Funded, Funded, &[Moderator]),
+    (Cancelled, Cancelled, &[Moderator]),
+    (Cancelled, Review, &[Moderator]),
+];
+
+const ALL_KINDS: [TimelineStatusKind; 8] = [
+    Draft,
+    Review,
+    Approved,
+    Rejected,
+    ApprovedConditionally,
+    PaymentProcessing,
+    Funded,
+    Cancelled,
+];
+
+fn edge_roles(from: TimelineStatusKind, to: TimelineStatusKind) -> &'static [TimelineRole] {
+    TRANSITIONS
+        .iter()
+        .find(|(edge_from, edge_to, _)| *edge_from == from && *edge_to == to)
+        .map_or(&[], |(_, _, roles)| roles)
+}
+
+/// Statuses the holder of `roles` may move a proposal in status `from` to.
+pub fn allowed_transitions(
+    from: TimelineStatusKind,
+    roles: &[TimelineRole],
+) -> Vec<TimelineStatusKind> {
+    ALL_KINDS
+        .into_iter()
+        .filter(|to| {
+            roles.contains(&Contract)
+                || edge_roles(from, *to).iter().any(|role| roles.contains(role))
+        })
+        .collect()
+}
+
+pub fn is_transition_allowed(
+    from: &TimelineStatus,
+    to: &TimelineStatus,
+    roles: &[TimelineRole],
+) -> bool {
+    if roles.contains(&Contract) {
+        return true;
+    }
+    edge_roles(from.kind(), to.kind()).iter().any(|role| {
+        roles.contains(role)
+            // Authors may only submit a proposal for review, not fill in the review itself.
+            && !(*role == Author && to.is_review() && !to.is_empty_review())
+    })
+}
+
+pub fn assert_transition_allowed(
+    from: &TimelineStatus,
+    to: &TimelineStatus,
+    roles: &[TimelineRole],
+) {
+    require!(
+        is_transition_allowed(from, to, roles),
+        format!("Transition from {} to {} is not allowed for this account", from.kind(), to.kind())
+    );
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+
+    use super::*;
+
+    fn status(value: serde_json::Value) -> TimelineStatus {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn checks_edges_per_role() {
+        let draft = status(json!({"status": "DRAFT"}));
+        let empty_review = status(json!({
+            "status": "REVIEW", "sponsor_requested_review": false,
+            "reviewer_completed_attestation": false, "kyc_verified": false
+        }));
+        let requested_review = status(json!({
+            "status": "REVIEW", "sponsor_requested_review": true,
+            "reviewer_completed_attestation": false, "kyc_verified": false
+        }));
+        let funded = status(json!({
+            "status": "FUNDED", "sponsor_requested_review": true,
+            "reviewer_completed_attestation": true, "kyc_verified": true,
+            "test_transaction_sent": true, "request_for_trustees_created": true,
+            "trustees_released_payment": true, "payouts": []
+        }));
+
+        assert!(is_transition_allowed(&draft, &empty_review, &[Author]));
+        assert!(!is_transition_allowed(&draft, &requested_review, &[Author]));
+        assert!(is_transition_allowed(&empty_review, &requested_review, &[RequestedSponsor]));
+        assert!(!is_transition_allowed(&empty_review, &draft, &[Author, Supervisor]));
+        assert!(!is_transition_allowed(&draft, &funded, &[Moderator]));
+        assert!(is_transition_allowed(&draft, &funded, &[Contract]));
+
+        assert_eq!(allowed_transitions(Review, &[Author]), vec![Cancelled]);
+        assert_eq!(allowed_transitions(Funded, &[Author, Supervisor]), vec![]);
+        assert_eq!(allowed_transitions(Funded, &[Contract]).len(), ALL_KINDS.len());
+    }
+}
@@ -0,0 +1,185 @@
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::{Proposal, ProposalId, ProposalRevision};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalTransfer {
+    pub previous_author: AccountId,
+    pub new_author: AccountId,
+    /// Moderator who forced the transfer, `None` when the new author accepted an offer.
+    pub forced_by: Option<AccountId>,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    /// First revision of the proposal under the new author.
+    pub revision: ProposalRevision,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default)]
+pub struct ProposalOwnership {
+    /// Account offered the proposal by its author.
+    pub pending_transfer: Option<AccountId>,
+    pub transfers: Vec<ProposalTransfer>,
+}
+
+impl Contract {
+    fn transfer_proposal_internal(
+        &mut self,
+        id: ProposalId,
+        new_author: AccountId,
+        forced_by: Option<AccountId>,
+    ) -> ProposalId {
+        let mut proposal = self.migrate_proposal_history(id);
+        let previous_author = std::mem::replace(&mut proposal.author_id, new_author.clone());
+        require!(previous_author != new_author, "The account is already the author");
+
+        let mut previous_proposals =
+            self.author_proposals.get(&previous_author).unwrap_or_default();
+        previous_proposals.remove(&id);
+        self.author_proposals.insert(&previous_author, &previous_proposals);
+        let mut new_proposals = self.author_proposals.get(&new_author).unwrap_or_default();
+        new_proposals.insert(id);
+        self.author_proposals.insert(&new_author, &new_proposals);
+        if self.is_proposal_coauthor(id, &new_author) {
+            let mut coauthors = self.proposal_coauthors.get(&id).unwrap_or_default();
+            coauthors.remove(&new_author);
+            self.proposal_coauthors.insert(&id, &coauthors);
+            let mut coauthored = self.coauthor_proposals.get(&new_author).unwrap_or_default();
+            coauthored.remove(&id);
+            self.coauthor_proposals.insert(&new_author, &coauthored);
+        }
+
+        // The transfer starts a new revision so that the history shows who made it and when.
+        let mut ownership = self.proposal_transfers.get(&id).unwrap_or_default();
+        ownership.pending_transfer = None;
+        ownership.transfers.push(ProposalTransfer {
+            previous_author: previous_author.clone(),
+            new_author: new_author.clone(),
+            forced_by,
+            timestamp: env::block_timestamp(),
+            revision: proposal.revision + 1,
+        });
+        self.proposal_transfers.insert(&id, &ownership);
+        self.update_fingerprint_author(id, &new_author);
+        let body = proposal.snapshot.body.clone();
+        let labels = proposal.snapshot.labels.clone();
+        self.save_proposal_snapshot(proposal, body, labels);
+
+        crate::notify::notify_proposal_transfer(id, previous_author, new_author);
+        id
+    }
+}
+
+#[near]
+impl Contract {
+    /// Offers the proposal to `new_author`, replacing any pending offer. The transfer happens once
+    /// the new author calls `accept_proposal_transfer`.
+    pub fn offer_proposal_transfer(&mut self, id: ProposalId, new_author: AccountId) {
+        let proposal: Proposal = self.get_proposal(id).into();
+        require!(
+            env::predecessor_account_id() == proposal.author_id,
+            "Only the author can transfer the proposal"
+        );
+        require!(new_author != proposal.author_id, "The account is already the author");
+        let mut ownership = self.proposal_transfers.get(&id).unwrap_or_default();
+        ownership.pending_transfer = Some(new_author);
+        self.proposal_transfers.insert(&id, &ownership);
+    }
+
+    #[payable]
+    pub fn accept_proposal_transfer(&mut self, id: ProposalId) -> ProposalId {
+        let new_author = env::predecessor_account_id();
+        let ownership = self.proposal_transfers.get(&id).unwrap_or_default();
+        require!(
+            ownership.pending_transfer.as_ref() == Some(&new_author),
+            "The proposal was not offered to this account"
+        );
+        self.transfer_proposal_internal(id, new_author, None)
+    }
+
+    #[payable]
+    pub fn force_proposal_transfer(&mut self, id: ProposalId, new_author: AccountId) -> ProposalId {
+        let moderator = env::predecessor_account_id();
+        require!(
+            self.has_moderator(moderator.clone()) || moderator == env::current_account_id(),
+            "Only the admin and moderators can force a transfer"
+        );
+        self.transfer_proposal_internal(id, new_author, Some(moderator))
+    }
+
+    pub fn get_proposal_ownership(&self, id: ProposalId) -> ProposalOwnership {
+        self.proposal_transfers.get(&id).unwrap_or_default()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::get_created_receipts;
+
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::{Contract, Proposal};
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        store_proposal(&mut contract, "bob.near", proposal_body(json!({})));
+        contract.author_proposals.insert(&"bob.near".parse().unwrap(), &HashSet::from([0]));
+        contract
+    }
+
+    #[test]
+    fn transfers_after_acceptance() {
+        let mut contract = setup();
+
+        set_predecessor("bob.near");
+        contract.offer_proposal_transfer(0, "alice.near".parse().unwrap());
+        set_predecessor("alice.near");
+        contract.accept_proposal_transfer(0);
+
+        let proposal: Proposal = contract.get_proposal(0).into();
+        assert_eq!(proposal.author_id.as_str(), "alice.near");
+        assert_eq!(proposal.revision, 1);
+        assert_eq!(contract.get_proposals_by_author("alice.near".parse().unwrap()), vec![0]);
+        assert!(contract.get_proposals_by_author("bob.near".parse().unwrap()).is_empty());
+        let ownership = contract.get_proposal_ownership(0);
+        assert!(ownership.pending_transfer.is_none());
+        assert_eq!(ownership.transfers[0].previous_author.as_str(), "bob.near");
+        assert_eq!(ownership.transfers[0].revision, 1);
+
+        let receipts = get_created_receipts();
+        let near_sdk::mock::MockAction::FunctionCallWeight { args, .. } = &receipts[0].actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        let args = String::from_utf8(args.clone()).unwrap();
+        assert!(args.contains("proposal/transfer"));
+    }
+
+    #[test]
+    fn moderators_force_transfers() {
+        let mut contract = setup();
+        set_predecessor("devhub.near");
+        contract.force_proposal_transfer(0, "carol.near".parse().unwrap());
+        let ownership = contract.get_proposal_ownership(0);
+        assert_eq!(ownership.transfers[0].forced_by.as_ref().unwrap().as_str(), "devhub.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal was not offered to this account")]
+    fn requires_an_offer() {
+        let mut contract = setup();
+        set_predecessor("bob.near");
+        contract.offer_proposal_transfer(0, "alice.near".parse().unwrap());
+        set_predecessor("carol.near");
+        contract.accept_proposal_transfer(0);
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{HashMap, HashSet};
+
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::timeline::TimelineStatus;
+use super::{Proposal, ProposalId};
+use crate::access_control::members::MembersList;
+use crate::common::charge_storage_deposit;
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VoteChoice {
+    Yes,
+    No,
+    Abstain,
+}
+
+/// Accounts allowed to vote on the proposals of a category.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VoterSet {
+    /// One vote per account.
+    AnyAccount,
+    /// Members of the `access_control` teams, including the members of their sub-teams.
+    Teams { teams: HashSet<String> },
+}
+
+impl VoterSet {
+    pub fn allows(&self, members_list: &MembersList, account_id: &AccountId) -> bool {
+        match self {
+            VoterSet::AnyAccount => true,
+            VoterSet::Teams { teams } => {
+                teams.iter().any(|team| members_list.is_in_team(account_id.clone(), team))
+            }
+        }
+    }
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct VotingPolicy {
+    /// Time in nanoseconds during which votes are accepted once the proposal enters review.
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub duration: u64,
+    pub voters: VoterSet,
+    /// Minimum number of votes, abstentions included, for the vote to be valid.
+    pub quorum: u32,
+    /// Whether the proposal can only be approved after reaching the quorum.
+    pub require_quorum_for_approval: bool,
+}
+
+/// Maximum number of characters of the comment of a vote.
+pub const MAX_VOTE_COMMENT_LENGTH: usize = 1000;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct Vote {
+    /// Voting round the vote was cast in, votes of earlier rounds are discarded.
+    pub round: u32,
+    pub choice: VoteChoice,
+    pub comment: Option<String>,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+}
+
+/// Voting round of the current review of a proposal. The votes themselves are stored per account
+/// in `Contract::account_votes`.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct ProposalVotes {
+    pub round: u32,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub opened_at: Timestamp,
+    pub tally: VoteTally,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct VoteTally {
+    pub yes: u32,
+    pub no: u32,
+    pub abstain: u32,
+}
+
+impl VoteTally {
+    pub fn total(&self) -> u32 {
+        self.yes + self.no + self.abstain
+    }
+
+    fn counter(&mut self, choice: VoteChoice) -> &mut u32 {
+        match choice {
+            VoteChoice::Yes => &mut self.yes,
+            VoteChoice::No => &mut self.no,
+            VoteChoice::Abstain => &mut self.abstain,
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn get_vote_tally(&self, id: ProposalId) -> VoteTally {
+        self.proposal_votes.get(&id).map(|votes| votes.tally).unwrap_or_default()
+    }
+
+    /// Starts a new round of votes, discarding the votes of a previous review.
+    pub(crate) fn open_voting_round(&mut self, id: ProposalId) {
+        let round = self.proposal_votes.get(&id).map_or(0, |votes| votes.round + 1);
+        self.proposal_votes.insert(
+            &id,
+            &ProposalVotes {
+                round,
+                opened_at: env::block_timestamp(),
+                tally: VoteTally::default(),
+            },
+        );
+    }
+
+    pub(crate) fn assert_voting_quorum(
+        &self,
+        id: ProposalId,
+        category: &String,
+        from: &TimelineStatus,
+        to: &TimelineStatus,
+    ) {
+        let approving =
+            matches!(to, TimelineStatus::Approved(..) | TimelineStatus::ApprovedConditionally(..));
+        if !from.is_review() || !approving {
+            return;
+        }
+        if let Some(policy) = self.voting_policies.get(category) {
+            require!(
+                !policy.require_quorum_for_approval
+                    || self.get_vote_tally(id).total() >= policy.quorum,
+                format!("The proposal has not reached the quorum of {} votes", policy.quorum)
+            );
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    /// Records or replaces the vote of the caller while the proposal is in review and the voting
+    /// window of its category is open. The attached deposit has to cover the storage of the vote,
+    /// the rest is refunded.
+    #[payable]
+    pub fn vote_on_proposal(
+        &mut self,
+        id: ProposalId,
+        choice: VoteChoice,
+        comment: Option<String>,
+    ) -> VoteTally {
+        let proposal: Proposal = self.get_proposal(id).into();
+        let body = proposal.snapshot.body.latest_version();
+        require!(
+            body.timeline.latest_version().is_review(),
+            "Votes are only accepted while the proposal is in review"
+        );
+        let policy = self
+            .voting_policies
+            .get(&body.category)
+            .unwrap_or_else(|| panic!("Voting is not enabled for the category {}", body.category));
+        let voter = env::predecessor_account_id();
+        require!(
+            policy.voters.allows(&self.access_control.members_list, &voter),
+            "The account is not allowed to vote on this proposal"
+        );
+        require!(
+            comment
+                .as_ref()
+                .map_or(true, |comment| comment.chars().count() <= MAX_VOTE_COMMENT_LENGTH),
+            format!("The comment cannot be longer than {} characters", MAX_VOTE_COMMENT_LENGTH)
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let now = env::block_timestamp();
+        // Proposals which entered review before voting was enabled open their round on the first
+        // vote.
+        let mut votes = self.proposal_votes.get(&id).unwrap_or(ProposalVotes {
+            round: 0,
+            opened_at: now,
+            tally: VoteTally::default(),
+        });
+        require!(
+            now < votes.opened_at.saturating_add(policy.duration),
+            "The voting window of this proposal is closed"
+        );
+        let key = (id, voter);
+        if let Some(previous) =
+            self.account_votes.get(&key).filter(|vote| vote.round == votes.round)
+        {
+            *votes.tally.counter(previous.choice) -= 1;
+        }
+        *votes.tally.counter(choice) += 1;
+        self.account_votes
+            .insert(&key, &Vote { round: votes.round, choice, comment, timestamp: now });
+        self.proposal_votes.insert(&id, &votes);
+        charge_storage_deposit(initial_storage_usage);
+        votes.tally
+    }
+
+    pub fn get_proposal_votes(&self, id: ProposalId) -> Option<ProposalVotes> {
+        self.proposal_votes.get(&id)
+    }
+
+    /// Returns the vote of the account in the current voting round of the proposal.
+    pub fn get_proposal_vote(&self, id: ProposalId, account_id: AccountId) -> Option<Vote> {
+        let round = self.proposal_votes.get(&id)?.round;
+        self.account_votes.get(&(id, account_id)).filter(|vote| vote.round == round)
+    }
+
+    pub fn get_proposal_vote_tally(&self, id: ProposalId) -> VoteTally {
+        self.get_vote_tally(id)
+    }
+
+    pub fn get_voting_policies(&self) -> HashMap<String, VotingPolicy> {
+        self.voting_policies.iter().collect()
+    }
+
+    /// Sets the voting policy of the category, `None` disables voting on its proposals.
+    #[payable]
+    pub fn set_voting_policy(&mut self, category: String, policy: Option<VotingPolicy>) {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can set voting policies"
+        );
+        self.assert_known_category(&category);
+        match policy {
+            Some(policy) => {
+                require!(policy.duration > 0, "The voting window cannot be empty");
+                self.voting_policies.insert(&category, &policy);
+            }
+            None => {
+                self.voting_policies.remove(&category);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashSet;
+
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::{AccountId, NearToken};
+
+    use super::{VoteChoice, VoteTally, MAX_VOTE_COMMENT_LENGTH};
+    use crate::access_control::members::{Member, MemberMetadata};
+    use crate::test_fixtures::{proposal_body, set_context, store_proposal};
+    use crate::Contract;
+
+    const DEPOSIT: NearToken = NearToken::from_millinear(10);
+
+    fn setup(voters: serde_json::Value) -> Contract {
+        set_context("devhub.near", 0, DEPOSIT);
+        let mut contract = Contract::new();
+        let body = proposal_body(json!({
+            "supervisor": "frol.near",
+            "timeline": {"status": "REVIEW", "sponsor_requested_review": true, "reviewer_completed_attestation": false},
+        }));
+        store_proposal(&mut contract, "bob.near", body);
+        contract.set_voting_policy(
+            "Marketing".to_string(),
+            serde_json::from_value(json!({
+                "duration": "100",
+                "voters": voters,
+                "quorum": 2,
+                "require_quorum_for_approval": true
+            }))
+            .unwrap(),
+        );
+        contract
+    }
+
+    fn approve(contract: &mut Contract) {
+        set_context("devhub.near", 50, DEPOSIT);
+        contract.edit_proposal_timeline(
+            0,
+            serde_json::from_value(json!({
+                "status": "APPROVED", "sponsor_requested_review": true, "reviewer_completed_attestation": true
+            }))
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn tallies_votes_and_checks_quorum() {
+        let mut contract = setup(json!({"type": "any_account"}));
+
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::No, None);
+        contract.vote_on_proposal(0, VoteChoice::Yes, Some("Changed my mind".to_string()));
+        set_context("carol.near", 20, DEPOSIT);
+        let tally = contract.vote_on_proposal(0, VoteChoice::Abstain, None);
+        assert_eq!(tally, VoteTally { yes: 1, no: 0, abstain: 1 });
+
+        let summary = contract.get_proposal_summaries(vec![0]).remove(0);
+        assert_eq!(summary.votes.total(), 2);
+        let votes = contract.get_proposal_votes(0).unwrap();
+        assert_eq!(votes.opened_at, 10);
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let vote = contract.get_proposal_vote(0, alice).unwrap();
+        assert_eq!(vote.comment.as_deref(), Some("Changed my mind"));
+
+        approve(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal has not reached the quorum of 2 votes")]
+    fn requires_quorum_for_approval() {
+        let mut contract = setup(json!({"type": "any_account"}));
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+        approve(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "The voting window of this proposal is closed")]
+    fn closes_voting_window() {
+        let mut contract = setup(json!({"type": "any_account"}));
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+        set_context("carol.near", 110, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+    }
+
+    #[test]
+    fn discards_votes_of_earlier_rounds() {
+        let mut contract = setup(json!({"type": "any_account"}));
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+
+        contract.open_voting_round(0);
+        assert_eq!(contract.get_proposal_vote_tally(0).total(), 0);
+        assert!(contract.get_proposal_vote(0, "alice.near".parse().unwrap()).is_none());
+        let tally = contract.vote_on_proposal(0, VoteChoice::No, None);
+        assert_eq!(tally, VoteTally { yes: 0, no: 1, abstain: 0 });
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover the storage")]
+    fn requires_storage_deposit() {
+        let mut contract = setup(json!({"type": "any_account"}));
+        set_context("alice.near", 10, NearToken::from_yoctonear(0));
+        contract.vote_on_proposal(0, VoteChoice::Yes, Some("Comment".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The comment cannot be longer than 1000 characters")]
+    fn limits_comment_length() {
+        let mut contract = setup(json!({"type": "any_account"}));
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(
+            0,
+            VoteChoice::Yes,
+            Some("a".repeat(MAX_VOTE_COMMENT_LENGTH + 1)),
+        );
+    }
+
+    fn setup_teams() -> Contract {
+        let mut contract = setup(json!({"type": "teams", "teams": ["voters"]}));
+        let members_list = &mut contract.access_control.members_list;
+        members_list.add_member(
+            Member::Account("alice.near".parse().unwrap()),
+            MemberMetadata::default().into(),
+        );
+        members_list.add_member(
+            Member::Team("reviewers".to_string()),
+            MemberMetadata {
+                children: HashSet::from([Member::Account("alice.near".parse().unwrap())]),
+                ..Default::default()
+            }
+            .into(),
+        );
+        members_list.add_member(
+            Member::Team("voters".to_string()),
+            MemberMetadata {
+                children: HashSet::from([Member::Team("reviewers".to_string())]),
+                ..Default::default()
+            }
+            .into(),
+        );
+        contract
+    }
+
+    #[test]
+    fn allows_members_of_sub_teams() {
+        let mut contract = setup_teams();
+        set_context("alice.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+        assert_eq!(contract.get_proposal_vote_tally(0).yes, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account is not allowed to vote on this proposal")]
+    fn rejects_accounts_outside_teams() {
+        let mut contract = setup_teams();
+        set_context("carol.near", 10, DEPOSIT);
+        contract.vote_on_proposal(0, VoteChoice::Yes, None);
+    }
+}
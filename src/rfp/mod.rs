@@ -1,12 +1,17 @@
+pub mod questions;
 pub mod repost;
+pub mod templates;
 pub mod timeline;
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
 pub use self::timeline::TimelineStatus;
+use self::timeline::{TimelineStatusV0, TimelineStatusV1};
 
 use crate::Contract;
-use crate::proposal::{Proposal, ProposalId, VersionedProposalBody};
+use crate::proposal::query::MAX_PAGE_SCAN;
+use crate::proposal::{Proposal, ProposalFundingCurrency, ProposalId, VersionedProposalBody};
 use crate::notify::get_text_mentions;
 use crate::str_serializers::*;
 
@@ -16,16 +21,19 @@ pub type RFPId = u32;
 
 type PostTag = String;
 
+pub type RFPRevision = u32;
+
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
 #[serde(tag = "rfp_version")]
 pub enum VersionedRFP {
-    V0(RFP),
+    V0(RFPV0),
+    V1(RFP),
 }
 
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
-pub struct RFP {
+pub struct RFPV0 {
     pub id: RFPId,
     pub author_id: AccountId,
     #[serde(
@@ -39,17 +47,108 @@ pub struct RFP {
     pub snapshot_history: Vec<BlockHeight>,
 }
 
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFP {
+    pub id: RFPId,
+    pub author_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub social_db_post_block_height: BlockHeight,
+    pub snapshot: RFPSnapshot,
+    // Revision of the current snapshot. Earlier revisions are stored in
+    // `Contract::rfp_revisions`.
+    pub revision: RFPRevision,
+    // Block heights of the snapshots an `RFPV0` had before its revisions were stored, oldest
+    // first. Empty for RFPs created after revisions were introduced.
+    pub legacy_block_heights: Vec<BlockHeight>,
+}
+
+/// Only the block heights of the earlier snapshots of an `RFPV0` are known, so its current
+/// snapshot becomes revision 0 and the heights are kept in `legacy_block_heights`.
+impl From<RFPV0> for RFP {
+    fn from(v0: RFPV0) -> Self {
+        RFP {
+            id: v0.id,
+            author_id: v0.author_id,
+            social_db_post_block_height: v0.social_db_post_block_height,
+            snapshot: v0.snapshot,
+            revision: 0,
+            legacy_block_heights: v0.snapshot_history,
+        }
+    }
+}
+
 impl From<VersionedRFP> for RFP {
     fn from(vp: VersionedRFP) -> Self {
         match vp {
-            VersionedRFP::V0(v0) => v0,
+            VersionedRFP::V0(v0) => v0.into(),
+            VersionedRFP::V1(v1) => v1,
         }
     }
 }
 
 impl From<RFP> for VersionedRFP {
     fn from(p: RFP) -> Self {
-        VersionedRFP::V0(p)
+        VersionedRFP::V1(p)
+    }
+}
+
+/// Lightweight view of the current state of an RFP which omits the description.
+#[near(serializers=[json])]
+#[derive(Clone)]
+pub struct RFPSummary {
+    pub id: RFPId,
+    pub author_id: AccountId,
+    pub name: String,
+    pub summary: String,
+    pub timeline: TimelineStatus,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub submission_deadline: Timestamp,
+    pub labels: HashSet<PostTag>,
+    pub linked_proposals: HashSet<ProposalId>,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub history_length: u32,
+}
+
+#[near(serializers=[json])]
+pub struct RFPSummariesPage {
+    pub rfps: Vec<RFPSummary>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more RFPs.
+    pub next_cursor: Option<RFPId>,
+}
+
+#[near(serializers=[json])]
+pub struct ClosedRFPsPage {
+    pub closed: Vec<RFPId>,
+    /// Id to pass as `cursor` to look at the next RFPs, `None` when every RFP was looked at.
+    pub next_cursor: Option<RFPId>,
+}
+
+impl From<RFP> for RFPSummary {
+    fn from(rfp: RFP) -> Self {
+        let body = rfp.snapshot.body.latest_version();
+        RFPSummary {
+            id: rfp.id,
+            author_id: rfp.author_id,
+            timeline: body.effective_timeline(env::block_timestamp()),
+            name: body.name,
+            summary: body.summary,
+            submission_deadline: body.submission_deadline,
+            labels: rfp.snapshot.labels,
+            linked_proposals: rfp.snapshot.linked_proposals,
+            timestamp: rfp.snapshot.timestamp,
+            history_length: rfp.revision,
+        }
     }
 }
 
@@ -79,7 +178,7 @@ pub struct RFPBodyV0 {
     pub name: String,
     pub summary: String,
     pub description: String,
-    pub timeline: TimelineStatus,
+    pub timeline: TimelineStatusV0,
     #[serde(
         serialize_with = "u64_dec_format::serialize",
         deserialize_with = "u64_dec_format::deserialize"
@@ -87,17 +186,94 @@ pub struct RFPBodyV0 {
     pub submission_deadline: Timestamp,
 }
 
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFPBodyV1 {
+    pub name: String,
+    pub summary: String,
+    pub description: String,
+    pub timeline: TimelineStatusV1,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub submission_deadline: Timestamp,
+    /// `None` for the RFPs created before budgets were introduced.
+    pub budget: Option<RFPBudget>,
+}
+
+/// Budget of an RFP. Amounts are in USD, like the `requested_sponsorship_usd_amount` of the
+/// proposals, and are paid in `currency`.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFPBudget {
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub min_usd_amount: u32,
+    #[serde(
+        serialize_with = "u32_dec_format::serialize",
+        deserialize_with = "u32_dec_format::deserialize"
+    )]
+    pub max_usd_amount: u32,
+    pub currency: ProposalFundingCurrency,
+    pub max_winners: u32,
+}
+
+impl RFPBudget {
+    pub fn assert_valid(&self) {
+        require!(
+            self.min_usd_amount <= self.max_usd_amount,
+            "The minimum budget cannot be greater than the maximum budget"
+        );
+        require!(self.max_winners > 0, "The RFP must allow at least one selected proposal");
+    }
+}
+
+#[near(serializers=[json])]
+pub struct RFPRemainingBudget {
+    pub rfp_id: RFPId,
+    pub currency: ProposalFundingCurrency,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub selected_usd_amount: u64,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub remaining_usd_amount: u64,
+    pub remaining_winners: u32,
+}
+
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
 #[serde(tag = "rfp_body_version")]
 pub enum VersionedRFPBody {
     V0(RFPBodyV0),
+    V1(RFPBodyV1),
 }
 
-impl From<VersionedRFPBody> for RFPBodyV0 {
+impl From<RFPBodyV0> for RFPBodyV1 {
+    fn from(v0: RFPBodyV0) -> Self {
+        RFPBodyV1 {
+            name: v0.name,
+            summary: v0.summary,
+            description: v0.description,
+            timeline: v0.timeline.into(),
+            submission_deadline: v0.submission_deadline,
+            budget: None,
+        }
+    }
+}
+
+impl From<VersionedRFPBody> for RFPBodyV1 {
     fn from(solution: VersionedRFPBody) -> Self {
         match solution {
-            VersionedRFPBody::V0(v0) => v0,
+            VersionedRFPBody::V0(v0) => v0.into(),
+            VersionedRFPBody::V1(v1) => v1,
         }
     }
 }
@@ -108,13 +284,47 @@ impl From<RFPBodyV0> for VersionedRFPBody {
     }
 }
 
+impl From<RFPBodyV1> for VersionedRFPBody {
+    fn from(p: RFPBodyV1) -> Self {
+        VersionedRFPBody::V1(p)
+    }
+}
+
 impl VersionedRFPBody {
-    pub fn latest_version(self) -> RFPBodyV0 {
+    pub fn latest_version(self) -> RFPBodyV1 {
         self.into()
     }
 }
 
-pub fn get_subscribers(proposal_body: &RFPBodyV0) -> Vec<String> {
+impl RFPBodyV1 {
+    pub fn is_past_submission_deadline(&self, now: Timestamp) -> bool {
+        now > self.submission_deadline
+    }
+
+    /// Status of the RFP at `now`: an RFP still accepting submissions after its deadline is
+    /// reported as being in evaluation, even before `close_expired_rfps` moves it there.
+    pub fn effective_timeline(&self, now: Timestamp) -> TimelineStatus {
+        if self.timeline.is_accepting_submissions() && self.is_past_submission_deadline(now) {
+            TimelineStatus::Evaluation
+        } else {
+            self.timeline.clone()
+        }
+    }
+}
+
+impl RFP {
+    /// The RFP as it is at `now`, reported in Evaluation once past its submission deadline.
+    /// Used by the views only, the stored RFP keeps its status until `close_expired_rfps`.
+    pub fn at(mut self, now: Timestamp) -> RFP {
+        let body = self.snapshot.body.clone().latest_version();
+        if body.timeline.is_accepting_submissions() && body.is_past_submission_deadline(now) {
+            self.snapshot.body = RFPBodyV1 { timeline: TimelineStatus::Evaluation, ..body }.into();
+        }
+        self
+    }
+}
+
+pub fn get_subscribers(proposal_body: &RFPBodyV1) -> Vec<String> {
     let result = [
         get_text_mentions(proposal_body.description.as_str()),
         get_text_mentions(proposal_body.summary.as_str()),
@@ -137,29 +347,110 @@ impl Contract {
                 .get(rfp_id.into())
                 .unwrap_or_else(|| panic!("RFP id {} not found", rfp_id))
                 .into();
+            let is_rfp_writer = self.is_allowed_to_write_rfps(env::predecessor_account_id());
+            let body = rfp.snapshot.body.latest_version();
             require!(
-                rfp.snapshot.body.latest_version().timeline.is_accepting_submissions() || self.is_allowed_to_write_rfps(env::predecessor_account_id()),
+                body.timeline.is_accepting_submissions() || is_rfp_writer,
                 format!("The RFP {} is not in the Accepting Submissions state, so you can't link or unlink to this RFP", rfp_id)
             );
+            require!(
+                !body.is_past_submission_deadline(env::block_timestamp()) || is_rfp_writer,
+                format!("The submission deadline of the RFP {} has passed, so you can't link or unlink to this RFP", rfp_id)
+            );
         }
     }
 
-    fn get_rfp_labels(&self, rfp_id: RFPId) -> HashSet<String> {
-        let rfp: RFP = self
-            .rfps
+    /// Moves up to `limit` RFPs whose submission deadline has passed from Accepting Submissions
+    /// to Evaluation, looking at no more than `MAX_PAGE_SCAN` RFPs starting from `cursor`, and
+    /// notifies the authors of their linked proposals. Returns the closed RFPs and the id to
+    /// continue from, `None` once every RFP was looked at.
+    pub(crate) fn close_expired_rfps_internal(
+        &mut self,
+        cursor: RFPId,
+        limit: u32,
+    ) -> (Vec<RFPId>, Option<RFPId>) {
+        let now = env::block_timestamp();
+        let total: RFPId = self.rfps.len().try_into().unwrap();
+        let end = total.min(cursor.saturating_add(MAX_PAGE_SCAN));
+        let mut expired = vec![];
+        let mut next_cursor = (end < total).then_some(end);
+        for id in cursor..end {
+            if expired.len() >= limit as usize {
+                next_cursor = Some(id);
+                break;
+            }
+            let rfp = self.load_rfp(id);
+            let body = rfp.snapshot.body.clone().latest_version();
+            if body.timeline.is_accepting_submissions() && body.is_past_submission_deadline(now) {
+                expired.push(rfp);
+            }
+        }
+
+        let mut notifications = vec![];
+        for rfp in expired {
+            let mut body = rfp.snapshot.body.clone().latest_version();
+            body.timeline = TimelineStatus::Evaluation;
+            let rfp_id = rfp.id;
+            let linked_proposals = rfp.snapshot.linked_proposals.clone();
+            let new_snapshot = RFPSnapshot {
+                editor_id: env::current_account_id(),
+                timestamp: now,
+                block_height: env::block_height(),
+                labels: rfp.snapshot.labels.clone(),
+                body: body.into(),
+                linked_proposals: linked_proposals.clone(),
+            };
+            self.save_rfp_snapshot(rfp, new_snapshot);
+
+            let authors: HashSet<AccountId> = linked_proposals
+                .iter()
+                .filter_map(|proposal_id| self.proposals.get((*proposal_id).into()))
+                .map(|proposal| Proposal::from(proposal).author_id)
+                .collect();
+            notifications.push((rfp_id, authors));
+        }
+        let closed = notifications.iter().map(|(rfp_id, _)| *rfp_id).collect();
+        crate::notify::notify_rfps_evaluation(notifications);
+        (closed, next_cursor)
+    }
+
+    pub(crate) fn load_rfp(&self, rfp_id: RFPId) -> RFP {
+        self.rfps
             .get(rfp_id.into())
             .unwrap_or_else(|| panic!("RFP id {} not found", rfp_id))
-            .into();
-        rfp.snapshot.labels
+            .into()
+    }
+
+    pub(crate) fn get_rfp_snapshot(&self, id: RFPId, revision: RFPRevision) -> Option<RFPSnapshot> {
+        let rfp: RFP = self.rfps.get(id.into())?.into();
+        match revision.cmp(&rfp.revision) {
+            Ordering::Less => self.rfp_revisions.get(&(id, revision)),
+            Ordering::Equal => Some(rfp.snapshot),
+            Ordering::Greater => None,
+        }
+    }
+
+    /// Makes `snapshot` the current snapshot of the RFP and moves the previous one to
+    /// `rfp_revisions`.
+    fn save_rfp_snapshot(&mut self, mut rfp: RFP, snapshot: RFPSnapshot) -> RFP {
+        let old_snapshot = std::mem::replace(&mut rfp.snapshot, snapshot);
+        self.rfp_revisions.insert(&(rfp.id, rfp.revision), &old_snapshot);
+        rfp.revision += 1;
+        self.rfps.replace(rfp.id.into(), &rfp.clone().into());
+        rfp
+    }
+
+    fn get_rfp_labels(&self, rfp_id: RFPId) -> HashSet<String> {
+        self.load_rfp(rfp_id).snapshot.labels
     }
 
     pub(crate) fn get_linked_proposals_in_rfp(&self, rfp_id: RFPId) -> HashSet<ProposalId> {
-        let rfp: RFP = self.get_rfp(rfp_id).into();
+        let rfp = self.load_rfp(rfp_id);
         rfp.snapshot.linked_proposals
     }
 
     fn change_linked_proposal_in_rfp(&mut self, rfp_id: RFPId, proposal_id: ProposalId, operation: LinkedProposalChangeOperation) {
-        let mut rfp: RFP = self.get_rfp(rfp_id).into();
+        let rfp = self.load_rfp(rfp_id);
         let mut linked_proposals = rfp.snapshot.linked_proposals.clone();
         match operation {
             LinkedProposalChangeOperation::Add => {
@@ -169,17 +460,15 @@ impl Contract {
                 linked_proposals.remove(&proposal_id);
             }
         }
-        rfp.snapshot_history.push(rfp.snapshot.block_height);
         let new_snapshot = RFPSnapshot {
             editor_id: env::predecessor_account_id(),
             timestamp: env::block_timestamp(),
             block_height: env::block_height(),
-            labels: rfp.snapshot.labels,
-            body: rfp.snapshot.body,
+            labels: rfp.snapshot.labels.clone(),
+            body: rfp.snapshot.body.clone(),
             linked_proposals: linked_proposals,
         };
-        rfp.snapshot = new_snapshot;
-        self.rfps.replace(rfp_id.try_into().unwrap(), &rfp.clone().into());
+        self.save_rfp_snapshot(rfp, new_snapshot);
     }
 
     fn add_linked_proposal_in_rfp(&mut self, rfp_id: RFPId, proposal_id: ProposalId) {
@@ -217,6 +506,74 @@ impl Contract {
         labels
     }
 
+    /// Checks that the proposals selected by `body` are linked to the RFP, approved, and fit
+    /// within the budget of the RFP.
+    fn assert_selected_proposals(&self, rfp: &RFP, body: &RFPBodyV1) {
+        let selected = body.timeline.get_selected_proposals();
+        require!(!selected.is_empty(), "Cannot change RFP status to Proposal Selected without selecting an approved proposal linked to this RFP");
+        let mut unique = selected.to_vec();
+        unique.sort();
+        unique.dedup();
+        require!(unique.len() == selected.len(), "A proposal is selected more than once");
+
+        let mut selected_usd_amount: u64 = 0;
+        for proposal_id in selected {
+            require!(
+                rfp.snapshot.linked_proposals.contains(proposal_id),
+                format!("Proposal {} is not linked to the RFP {}", proposal_id, rfp.id)
+            );
+            let proposal: Proposal = self.get_proposal(*proposal_id).into();
+            let proposal_body = proposal.snapshot.body.latest_version();
+            require!(
+                proposal_body.timeline.latest_version().was_approved(),
+                format!("Proposal {} is not approved", proposal_id)
+            );
+            if let Some(budget) = &body.budget {
+                require!(
+                    proposal_body.requested_sponsorship_paid_in_currency == budget.currency,
+                    format!("Proposal {} is not paid in the currency of the RFP budget", proposal_id)
+                );
+            }
+            selected_usd_amount += u64::from(proposal_body.requested_sponsorship_usd_amount);
+        }
+
+        if let Some(budget) = &body.budget {
+            require!(
+                selected.len() <= budget.max_winners as usize,
+                format!("The RFP {} allows at most {} selected proposals", rfp.id, budget.max_winners)
+            );
+            require!(
+                selected_usd_amount <= u64::from(budget.max_usd_amount),
+                format!("The selected proposals request {} USD, over the budget of {} USD", selected_usd_amount, budget.max_usd_amount)
+            );
+            require!(
+                selected_usd_amount >= u64::from(budget.min_usd_amount),
+                format!("The selected proposals request {} USD, under the budget of {} USD", selected_usd_amount, budget.min_usd_amount)
+            );
+        }
+    }
+
+    pub(crate) fn get_rfp_remaining_budget_internal(&self, rfp_id: RFPId) -> Option<RFPRemainingBudget> {
+        let rfp = self.load_rfp(rfp_id);
+        let body = rfp.snapshot.body.latest_version();
+        let budget = body.budget?;
+        let selected = body.timeline.get_selected_proposals();
+        let selected_usd_amount: u64 = selected
+            .iter()
+            .filter_map(|proposal_id| self.proposals.get((*proposal_id).into()))
+            .map(|proposal| {
+                u64::from(Proposal::from(proposal).snapshot.body.latest_version().requested_sponsorship_usd_amount)
+            })
+            .sum();
+        Some(RFPRemainingBudget {
+            rfp_id,
+            currency: budget.currency,
+            selected_usd_amount,
+            remaining_usd_amount: u64::from(budget.max_usd_amount).saturating_sub(selected_usd_amount),
+            remaining_winners: budget.max_winners.saturating_sub(selected.len().try_into().unwrap()),
+        })
+    }
+
     pub(crate) fn edit_rfp_internal(
         &mut self,
         id: RFPId,
@@ -229,22 +586,24 @@ impl Contract {
             "The account is not allowed to edit RFPs"
         );
 
-        let mut rfp: RFP = self.get_rfp(id).into();
+        let rfp = self.load_rfp(id);
 
         let rfp_body = body.clone().latest_version();
 
+        if let Some(budget) = &rfp_body.budget {
+            budget.assert_valid();
+        }
         if rfp_body.timeline.is_proposal_selected() {
-            let has_approved_proposal = self.get_rfp_linked_proposals(id)
-                .into_iter()
-                .filter_map(|proposal_id| self.proposals.get(proposal_id.into()))
-                .any(|proposal|  Into::<Proposal>::into(proposal).snapshot.body.latest_version().timeline.latest_version().was_approved());
-            require!(has_approved_proposal, "Cannot change RFP status to Proposal Selected without an approved proposal linked to this RFP");
+            self.assert_selected_proposals(&rfp, &rfp_body);
         }
 
         let old_snapshot = rfp.snapshot.clone();
+        let old_body = old_snapshot.body.clone().latest_version();
+        let text_changed = rfp_body.name != old_body.name
+            || rfp_body.summary != old_body.summary
+            || rfp_body.description != old_body.description;
         let old_labels_set = old_snapshot.labels.clone();
         let new_labels = labels;
-        rfp.snapshot_history.push(rfp.snapshot.block_height);
         let new_snapshot = RFPSnapshot {
             editor_id: env::predecessor_account_id(),
             timestamp: env::block_timestamp(),
@@ -253,8 +612,10 @@ impl Contract {
             body: body,
             linked_proposals: old_snapshot.linked_proposals.clone(),
         };
-        rfp.snapshot = new_snapshot;
-        self.rfps.replace(id.try_into().unwrap(), &rfp.clone().into());
+        let rfp = self.save_rfp_snapshot(rfp, new_snapshot);
+        if text_changed {
+            self.index_rfp_search(&rfp);
+        }
 
         // Update labels index.
         let new_labels_set = new_labels;
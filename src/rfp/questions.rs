@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+
+use near_sdk::{env, near, require, AccountId, Timestamp};
+
+use super::RFPId;
+use crate::common::charge_storage_deposit;
+use crate::proposal::query::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt, Proposal};
+
+pub type RFPQuestionId = u32;
+
+/// Maximum number of characters of a question.
+pub const MAX_QUESTION_LENGTH: usize = 2000;
+
+/// Clarification about the scope of an RFP asked by a prospective applicant.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFPQuestion {
+    pub id: RFPQuestionId,
+    pub author_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub text: String,
+    pub answer: Option<RFPAnswer>,
+}
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFPAnswer {
+    pub author_id: AccountId,
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub timestamp: Timestamp,
+    pub text: String,
+}
+
+#[near(serializers=[json])]
+pub struct RFPQuestionsPage {
+    pub questions: Vec<RFPQuestion>,
+    /// Id to pass as `cursor` to fetch the next page, `None` when there are no more questions.
+    pub next_cursor: Option<RFPQuestionId>,
+}
+
+#[near]
+impl Contract {
+    /// The attached deposit has to cover the storage of the question, the rest is refunded.
+    #[payable]
+    pub fn ask_rfp_question(&mut self, rfp_id: RFPId, text: String) -> RFPQuestionId {
+        self.load_rfp(rfp_id);
+        require!(!text.trim().is_empty(), "The question cannot be empty");
+        require!(
+            text.chars().count() <= MAX_QUESTION_LENGTH,
+            format!("The question cannot be longer than {} characters", MAX_QUESTION_LENGTH)
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let id = self.rfp_question_counts.get(&rfp_id).unwrap_or(0);
+        self.rfp_questions.insert(
+            &(rfp_id, id),
+            &RFPQuestion {
+                id,
+                author_id: env::predecessor_account_id(),
+                timestamp: env::block_timestamp(),
+                text,
+                answer: None,
+            },
+        );
+        self.rfp_question_counts.insert(&rfp_id, &(id + 1));
+        charge_storage_deposit(initial_storage_usage);
+        id
+    }
+
+    /// Publishes the answer and notifies the asker and the authors of the proposals linked to the
+    /// RFP.
+    #[payable]
+    pub fn answer_rfp_question(
+        &mut self,
+        rfp_id: RFPId,
+        question_id: RFPQuestionId,
+        text: String,
+    ) -> RFPQuestionId {
+        let author_id = env::predecessor_account_id();
+        require!(
+            self.is_allowed_to_write_rfps(author_id.clone()),
+            "The account is not allowed to answer RFP questions"
+        );
+        require!(!text.trim().is_empty(), "The answer cannot be empty");
+
+        let mut question = self
+            .rfp_questions
+            .get(&(rfp_id, question_id))
+            .unwrap_or_else(|| panic!("Question {} of RFP id {} not found", question_id, rfp_id));
+        require!(question.answer.is_none(), "The question is already answered");
+        question.answer = Some(RFPAnswer { author_id, timestamp: env::block_timestamp(), text });
+        self.rfp_questions.insert(&(rfp_id, question_id), &question);
+
+        let rfp = self.load_rfp(rfp_id);
+        let mut accounts: HashSet<AccountId> = rfp
+            .snapshot
+            .linked_proposals
+            .iter()
+            .filter_map(|proposal_id| self.proposals.get((*proposal_id).into()))
+            .map(|proposal| Proposal::from(proposal).author_id)
+            .collect();
+        accounts.insert(question.author_id);
+        crate::notify::notify_rfp_answer(rfp_id, question_id, accounts);
+        question_id
+    }
+
+    pub fn get_rfp_questions(
+        &self,
+        rfp_id: RFPId,
+        cursor: Option<RFPQuestionId>,
+        limit: Option<u32>,
+    ) -> RFPQuestionsPage {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        let total = self.rfp_question_counts.get(&rfp_id).unwrap_or(0);
+        let start = cursor.unwrap_or(0).min(total);
+        let end = start.saturating_add(limit).min(total);
+        RFPQuestionsPage {
+            questions: (start..end)
+                .filter_map(|id| self.rfp_questions.get(&(rfp_id, id)))
+                .collect(),
+            next_cursor: if end < total { Some(end) } else { None },
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, NearToken};
+
+    use super::MAX_QUESTION_LENGTH;
+    use crate::rfp::{RFPSnapshot, RFP};
+    use crate::Contract;
+
+    fn set_predecessor(account: &str) {
+        set_context(account, NearToken::from_millinear(10));
+    }
+
+    fn set_context(account: &str, deposit: NearToken) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("devhub.near".parse().unwrap())
+            .predecessor_account_id(account.parse().unwrap())
+            .attached_deposit(deposit)
+            .block_timestamp(1000)
+            .build());
+    }
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        let mut contract = Contract::new();
+        let snapshot: RFPSnapshot = serde_json::from_value(json!({
+            "editor_id": "devhub.near",
+            "timestamp": "0",
+            "block_height": "0",
+            "labels": [],
+            "rfp_body_version": "V0",
+            "name": "rfp",
+            "summary": "sum",
+            "description": "description",
+            "submission_deadline": "2000",
+            "timeline": {"status": "ACCEPTING_SUBMISSIONS"},
+            "linked_proposals": [],
+        }))
+        .unwrap();
+        let rfp = RFP {
+            id: 0,
+            author_id: "devhub.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot,
+            revision: 0,
+            legacy_block_heights: vec![],
+        };
+        contract.rfps.push(&rfp.into());
+        contract
+    }
+
+    #[test]
+    fn asks_and_answers() {
+        let mut contract = setup();
+
+        set_predecessor("alice.near");
+        let id = contract.ask_rfp_question(0, "Is a testnet deployment enough?".to_string());
+        set_predecessor("devhub.near");
+        contract.answer_rfp_question(0, id, "Mainnet is required.".to_string());
+
+        let questions = contract.get_rfp_questions(0, None, None).questions;
+        let answer = questions[0].answer.as_ref().unwrap();
+        assert_eq!(answer.text, "Mainnet is required.");
+        assert_eq!(answer.timestamp, 1000);
+        assert_eq!(answer.author_id.as_str(), "devhub.near");
+
+        let receipts = get_created_receipts();
+        let near_sdk::mock::MockAction::FunctionCallWeight { args, .. } = &receipts[0].actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        let args = String::from_utf8(args.clone()).unwrap();
+        assert!(args.contains("rfp/answer"));
+        assert!(args.contains("alice.near"));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account is not allowed to answer RFP questions")]
+    fn only_rfp_writers_answer() {
+        let mut contract = setup();
+
+        set_predecessor("alice.near");
+        let id = contract.ask_rfp_question(0, "Question".to_string());
+        contract.answer_rfp_question(0, id, "Answer".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover the storage")]
+    fn questions_require_storage_deposit() {
+        let mut contract = setup();
+
+        set_context("alice.near", NearToken::from_yoctonear(0));
+        contract.ask_rfp_question(0, "Question".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The question cannot be longer than 2000 characters")]
+    fn limits_question_length() {
+        let mut contract = setup();
+
+        set_predecessor("alice.near");
+        contract.ask_rfp_question(0, "a".repeat(MAX_QUESTION_LENGTH + 1));
+    }
+
+    #[test]
+    fn pages_questions() {
+        let mut contract = setup();
+
+        set_predecessor("alice.near");
+        for i in 0..3 {
+            contract.ask_rfp_question(0, format!("Question {}", i));
+        }
+
+        let page = contract.get_rfp_questions(0, None, Some(2));
+        assert_eq!(page.questions.len(), 2);
+        assert_eq!(page.next_cursor, Some(2));
+        let page = contract.get_rfp_questions(0, page.next_cursor, Some(2));
+        assert_eq!(page.questions[0].text, "Question 2");
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+
+use near_sdk::json_types::U64;
+use near_sdk::{env, near, require, Promise};
+
+use super::{RFPBodyV1, RFPBudget, TimelineStatus};
+use crate::str_serializers::*;
+use crate::{Contract, ContractExt};
+
+pub type RFPTemplateId = u32;
+
+/// Skeleton of the RFPs of a repeated funding round. `summary` and `description` may contain
+/// `{{key}}` placeholders which are filled in by `add_rfp_from_template`.
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+pub struct RFPTemplate {
+    pub name: String,
+    pub summary: String,
+    pub description: String,
+    pub labels: HashSet<String>,
+    /// Time in nanoseconds between the creation of an RFP and its submission deadline.
+    #[serde(
+        serialize_with = "u64_dec_format::serialize",
+        deserialize_with = "u64_dec_format::deserialize"
+    )]
+    pub deadline_offset: u64,
+    pub budget: Option<RFPBudget>,
+}
+
+#[near(serializers=[json])]
+pub struct RFPTemplateInfo {
+    pub id: RFPTemplateId,
+    #[serde(flatten)]
+    pub template: RFPTemplate,
+}
+
+/// Values replacing the ones of the template. Fields left `None` keep the template value.
+#[near(serializers=[json])]
+#[derive(Default)]
+pub struct RFPTemplateOverrides {
+    pub name: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub labels: Option<HashSet<String>>,
+    pub submission_deadline: Option<U64>,
+    pub budget: Option<RFPBudget>,
+    /// Values of the `{{key}}` placeholders of the summary and the description.
+    #[serde(default)]
+    pub placeholders: HashMap<String, String>,
+}
+
+/// Replaces the `{{key}}` placeholders of `text` and fails on the ones without a value.
+pub fn fill_placeholders(text: &str, values: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + end].trim();
+        let value =
+            values.get(key).unwrap_or_else(|| panic!("Missing value for the placeholder {}", key));
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+impl Contract {
+    fn assert_valid_rfp_template(&self, template: &RFPTemplate) {
+        require!(
+            self.is_allowed_to_write_rfps(env::predecessor_account_id()),
+            "The account is not allowed to manage RFP templates"
+        );
+        for label in &template.labels {
+            require!(
+                self.global_labels_info.get().get(label).is_some(),
+                format!("Label {} is not registered", label)
+            );
+        }
+        if let Some(budget) = &template.budget {
+            budget.assert_valid();
+        }
+    }
+}
+
+#[near]
+impl Contract {
+    pub fn create_rfp_template(&mut self, template: RFPTemplate) -> RFPTemplateId {
+        self.assert_valid_rfp_template(&template);
+        let id: RFPTemplateId = self.rfp_templates.len().try_into().unwrap();
+        self.rfp_templates.push(&template);
+        id
+    }
+
+    pub fn update_rfp_template(
+        &mut self,
+        template_id: RFPTemplateId,
+        template: RFPTemplate,
+    ) -> RFPTemplateId {
+        require!(
+            template_id < self.rfp_templates.len().try_into().unwrap(),
+            format!("RFP template id {} not found", template_id)
+        );
+        self.assert_valid_rfp_template(&template);
+        self.rfp_templates.replace(template_id.into(), &template);
+        template_id
+    }
+
+    pub fn list_rfp_templates(&self) -> Vec<RFPTemplateInfo> {
+        self.rfp_templates
+            .iter()
+            .enumerate()
+            .map(|(id, template)| RFPTemplateInfo { id: id.try_into().unwrap(), template })
+            .collect()
+    }
+
+    /// Creates an RFP accepting submissions from the template, like `add_rfp` does.
+    #[payable]
+    pub fn add_rfp_from_template(
+        &mut self,
+        template_id: RFPTemplateId,
+        overrides: Option<RFPTemplateOverrides>,
+    ) -> Promise {
+        let template = self
+            .rfp_templates
+            .get(template_id.into())
+            .unwrap_or_else(|| panic!("RFP template id {} not found", template_id));
+        let overrides = overrides.unwrap_or_default();
+        let summary = overrides.summary.unwrap_or(template.summary);
+        let description = overrides.description.unwrap_or(template.description);
+        let body = RFPBodyV1 {
+            name: overrides.name.unwrap_or(template.name),
+            summary: fill_placeholders(&summary, &overrides.placeholders),
+            description: fill_placeholders(&description, &overrides.placeholders),
+            timeline: TimelineStatus::AcceptingSubmissions,
+            submission_deadline: overrides.submission_deadline.map_or_else(
+                || env::block_timestamp().saturating_add(template.deadline_offset),
+                u64::from,
+            ),
+            budget: overrides.budget.or(template.budget),
+        };
+        self.add_rfp(body.into(), overrides.labels.unwrap_or(template.labels))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashMap;
+
+    use near_sdk::serde_json::{self, json};
+    use near_sdk::test_utils::{get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::fill_placeholders;
+    use crate::Contract;
+
+    #[test]
+    fn adds_rfp_from_template() {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("devhub.near".parse().unwrap())
+            .predecessor_account_id("devhub.near".parse().unwrap())
+            .build());
+        let mut contract = Contract::new();
+        let id = contract.create_rfp_template(
+            serde_json::from_value(json!({
+                "name": "Tooling round",
+                "summary": "Tooling grants for {{quarter}}",
+                "description": "Build tools",
+                "labels": [],
+                "deadline_offset": "1000",
+                "budget": null
+            }))
+            .unwrap(),
+        );
+        assert_eq!(contract.list_rfp_templates()[0].template.name, "Tooling round");
+
+        contract.add_rfp_from_template(
+            id,
+            serde_json::from_value(json!({"placeholders": {"quarter": "Q3"}})).unwrap(),
+        );
+        let receipts = get_created_receipts();
+        let near_sdk::mock::MockAction::FunctionCallWeight { args, .. } = &receipts[0].actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        let args = String::from_utf8(args.clone()).unwrap();
+        assert!(args.contains("Tooling grants for Q3"));
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        let values = HashMap::from([
+            ("quarter".to_string(), "Q3".to_string()),
+            ("year".to_string(), "2024".to_string()),
+        ]);
+        assert_eq!(
+            fill_placeholders("Funding round {{quarter}} {{ year }}, {no placeholder}", &values),
+            "Funding round Q3 2024, {no placeholder}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing value for the placeholder quarter")]
+    fn fails_on_missing_placeholder() {
+        fill_placeholders("Round {{quarter}}", &HashMap::new());
+    }
+}
@@ -1,15 +1,49 @@
 use near_sdk::near;
 
+use crate::proposal::ProposalId;
+
 #[near(serializers=[borsh, json])]
 #[derive(Clone)]
 #[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
-pub enum TimelineStatus {
+pub enum TimelineStatusV0 {
     AcceptingSubmissions,
     Evaluation,
     ProposalSelected,
     Cancelled,
 }
 
+#[near(serializers=[borsh, json])]
+#[derive(Clone)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TimelineStatusV1 {
+    AcceptingSubmissions,
+    Evaluation,
+    ProposalSelected(SelectedProposals),
+    Cancelled,
+}
+
+pub type TimelineStatus = TimelineStatusV1;
+
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default)]
+pub struct SelectedProposals {
+    pub selected_proposals: Vec<ProposalId>,
+}
+
+impl From<TimelineStatusV0> for TimelineStatusV1 {
+    fn from(v0: TimelineStatusV0) -> Self {
+        match v0 {
+            TimelineStatusV0::AcceptingSubmissions => TimelineStatusV1::AcceptingSubmissions,
+            TimelineStatusV0::Evaluation => TimelineStatusV1::Evaluation,
+            // The winners of the RFPs selected before V1 were not recorded.
+            TimelineStatusV0::ProposalSelected => {
+                TimelineStatusV1::ProposalSelected(SelectedProposals::default())
+            }
+            TimelineStatusV0::Cancelled => TimelineStatusV1::Cancelled,
+        }
+    }
+}
+
 impl TimelineStatus {
     pub fn is_accepting_submissions(&self) -> bool {
         matches!(self, TimelineStatus::AcceptingSubmissions)
@@ -20,6 +54,13 @@ impl TimelineStatus {
     }
 
     pub fn is_proposal_selected(&self) -> bool {
-        matches!(self, TimelineStatus::ProposalSelected)
+        matches!(self, TimelineStatus::ProposalSelected(..))
+    }
+
+    pub fn get_selected_proposals(&self) -> &[ProposalId] {
+        match self {
+            TimelineStatus::ProposalSelected(selected) => &selected.selected_proposals,
+            _ => &[],
+        }
     }
 }
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use near_sdk::collections::LookupMap;
+use near_sdk::{env, near, require, AccountId, Gas};
+
+use crate::proposal::query::{ProposalFilters, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT, MAX_PAGE_SCAN};
+use crate::proposal::similarity::proposal_text;
+use crate::proposal::{Proposal, ProposalId, ProposalSummary};
+use crate::rfp::{RFPId, RFPSummary, RFP};
+use crate::{Contract, ContractExt};
+
+/// Words too common to tell records apart. They are skipped both when indexing and searching.
+pub const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been",
+    "but", "by", "can", "do", "for", "from", "has", "have", "how", "if", "in", "into", "is", "it",
+    "its", "more", "not", "of", "on", "or", "other", "our", "so", "such", "than", "that", "the",
+    "their", "them", "then", "there", "these", "they", "this", "to", "us", "was", "we", "were",
+    "what", "when", "which", "will", "with", "would", "you", "your",
+];
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 32;
+/// Only the most frequent terms of a record are indexed to bound the storage writes of an edit.
+const MAX_TERMS_PER_DOCUMENT: usize = 100;
+const MAX_QUERY_TERMS: usize = 10;
+/// Terms found in more records are too common to narrow a search and are not indexed any further,
+/// which bounds the size of a posting list.
+const MAX_TERM_POSTINGS: usize = 1000;
+/// Gas left untouched by a batch of `backfill_search_index` so that it can record its progress.
+const BACKFILL_GAS_RESERVE: Gas = Gas::from_tgas(20);
+
+/// Record indexed by the keyword search.
+#[near(serializers=[borsh])]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchDocument {
+    Proposal(ProposalId),
+    RFP(RFPId),
+}
+
+/// Position of `backfill_search_index` among the records created before the search index.
+#[near(serializers=[borsh, json])]
+#[derive(Clone, Default)]
+pub struct SearchBackfill {
+    pub next_proposal: ProposalId,
+    pub next_rfp: RFPId,
+    pub done: bool,
+}
+
+#[near(serializers=[json])]
+#[derive(Clone, Default)]
+pub struct RFPSearchFilters {
+    #[serde(default)]
+    pub author_id: Option<AccountId>,
+    /// Only RFPs that have all of these labels.
+    #[serde(default)]
+    pub labels: Option<Vec<String>>,
+}
+
+impl RFPSearchFilters {
+    pub fn matches(&self, rfp: &RFP) -> bool {
+        self.author_id.as_ref().map_or(true, |author_id| &rfp.author_id == author_id)
+            && self.labels.iter().flatten().all(|label| rfp.snapshot.labels.contains(label))
+    }
+}
+
+#[near(serializers=[json])]
+pub struct ProposalSearchPage {
+    /// Matching proposals, the most relevant first.
+    pub proposals: Vec<ProposalSummary>,
+    /// Offset to pass as `cursor` to fetch the next page, `None` when there are no more results.
+    pub next_cursor: Option<u32>,
+}
+
+#[near(serializers=[json])]
+pub struct RFPSearchPage {
+    /// Matching RFPs, the most relevant first.
+    pub rfps: Vec<RFPSummary>,
+    /// Offset to pass as `cursor` to fetch the next page, `None` when there are no more results.
+    pub next_cursor: Option<u32>,
+}
+
+/// Lowercase words of the text which are neither stopwords nor too short or too long to be
+/// useful search terms.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| (MIN_TERM_LENGTH..=MAX_TERM_LENGTH).contains(&word.chars().count()))
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+}
+
+/// The most frequent terms of the text with their number of occurrences.
+pub fn document_terms(text: &str) -> Vec<(String, u32)> {
+    let mut frequencies: HashMap<String, u32> = HashMap::new();
+    for term in tokenize(text) {
+        *frequencies.entry(term).or_insert(0) += 1;
+    }
+    let mut terms: Vec<(String, u32)> = frequencies.into_iter().collect();
+    terms.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    terms.truncate(MAX_TERMS_PER_DOCUMENT);
+    terms
+}
+
+fn query_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = vec![];
+    for term in tokenize(query) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms.truncate(MAX_QUERY_TERMS);
+    terms
+}
+
+fn rfp_text(rfp: &RFP) -> String {
+    let body = rfp.snapshot.body.clone().latest_version();
+    format!("{}\n{}\n{}", body.name, body.summary, body.description)
+}
+
+/// Ids of the records containing any of the terms. Records matching more terms come first, then
+/// the ones where the terms occur more often.
+fn rank(index: &LookupMap<String, HashMap<u32, u32>>, terms: &[String]) -> Vec<u32> {
+    let mut scores: HashMap<u32, (u32, u32)> = HashMap::new();
+    for term in terms {
+        for (id, frequency) in index.get(term).unwrap_or_default() {
+            let score = scores.entry(id).or_insert((0, 0));
+            score.0 += 1;
+            score.1 += frequency;
+        }
+    }
+    let mut ranked: Vec<(u32, (u32, u32))> = scores.into_iter().collect();
+    ranked.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(b.cmp(a)));
+    ranked.into_iter().map(|(id, _)| id).collect()
+}
+
+/// Walks the ranked ids from `cursor`, keeping the records accepted by `load` until the page is
+/// full or `MAX_PAGE_SCAN` records were looked at.
+fn search_page<T>(
+    ranked: Vec<u32>,
+    cursor: Option<u32>,
+    limit: Option<u32>,
+    mut load: impl FnMut(u32) -> Option<T>,
+) -> (Vec<T>, Option<u32>) {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+    let start = (cursor.unwrap_or(0) as usize).min(ranked.len());
+    let mut result = vec![];
+    for (position, id) in ranked.iter().enumerate().skip(start) {
+        if result.len() >= limit || position - start >= MAX_PAGE_SCAN as usize {
+            return (result, Some(position.try_into().unwrap()));
+        }
+        result.extend(load(*id));
+    }
+    (result, None)
+}
+
+impl Contract {
+    /// Replaces the indexed terms of the record with the terms of `text`.
+    pub(crate) fn index_search_document(&mut self, document: SearchDocument, text: &str) {
+        let (index, id) = match document {
+            SearchDocument::Proposal(id) => (&mut self.proposal_search_index, id),
+            SearchDocument::RFP(id) => (&mut self.rfp_search_index, id),
+        };
+        for term in self.search_terms.get(&document).unwrap_or_default() {
+            let mut postings = index.get(&term).unwrap_or_default();
+            postings.remove(&id);
+            if postings.is_empty() {
+                index.remove(&term);
+            } else {
+                index.insert(&term, &postings);
+            }
+        }
+        let terms = document_terms(text);
+        for (term, frequency) in &terms {
+            let mut postings = index.get(term).unwrap_or_default();
+            if postings.len() < MAX_TERM_POSTINGS {
+                postings.insert(id, *frequency);
+                index.insert(term, &postings);
+            }
+        }
+        self.search_terms.insert(&document, &terms.into_iter().map(|(term, _)| term).collect());
+    }
+
+    pub(crate) fn index_proposal_search(&mut self, proposal: &Proposal) {
+        let text = proposal_text(&proposal.snapshot.body.clone().latest_version());
+        self.index_search_document(SearchDocument::Proposal(proposal.id), &text);
+    }
+
+    pub(crate) fn index_rfp_search(&mut self, rfp: &RFP) {
+        self.index_search_document(SearchDocument::RFP(rfp.id), &rfp_text(rfp));
+    }
+}
+
+#[near]
+impl Contract {
+    pub fn search_proposals(
+        &self,
+        query: String,
+        filters: Option<ProposalFilters>,
+        limit: Option<u32>,
+        cursor: Option<u32>,
+    ) -> ProposalSearchPage {
+        let filters = filters.unwrap_or_default();
+        let ranked = rank(&self.proposal_search_index, &query_terms(&query));
+        let (proposals, next_cursor) = search_page(ranked, cursor, limit, |id| {
+            let proposal: Proposal = self.proposals.get(id.into())?.into();
+            let created_timestamp = || {
+                self.get_proposal_snapshot(id, 0)
+                    .map_or(proposal.snapshot.timestamp, |snapshot| snapshot.timestamp)
+            };
+            filters
+                .matches(&proposal, created_timestamp)
+                .then(|| self.get_proposal_summary(proposal.clone()))
+        });
+        ProposalSearchPage { proposals, next_cursor }
+    }
+
+    pub fn search_rfps(
+        &self,
+        query: String,
+        filters: Option<RFPSearchFilters>,
+        limit: Option<u32>,
+        cursor: Option<u32>,
+    ) -> RFPSearchPage {
+        let filters = filters.unwrap_or_default();
+        let ranked = rank(&self.rfp_search_index, &query_terms(&query));
+        let (rfps, next_cursor) = search_page(ranked, cursor, limit, |id| {
+            let rfp: RFP = self.rfps.get(id.into())?.into();
+            filters.matches(&rfp).then(|| rfp.into())
+        });
+        RFPSearchPage { rfps, next_cursor }
+    }
+
+    /// Indexes up to `limit` of the proposals, then of the RFPs, created before the search index
+    /// and returns the progress. Call it again until `done` is set.
+    pub fn backfill_search_index(&mut self, limit: Option<u32>) -> SearchBackfill {
+        let editor_id = env::predecessor_account_id();
+        require!(
+            self.has_moderator(editor_id.clone()) || editor_id == env::current_account_id(),
+            "Only the admin and moderators can backfill the search index"
+        );
+        let mut progress = self.search_backfill.clone();
+        let proposals_count: ProposalId = self.proposals.len().try_into().unwrap();
+        let rfps_count: RFPId = self.rfps.len().try_into().unwrap();
+        let mut remaining = limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        while remaining > 0
+            && env::prepaid_gas().saturating_sub(env::used_gas()) > BACKFILL_GAS_RESERVE
+        {
+            if progress.next_proposal < proposals_count {
+                let proposal: Proposal =
+                    self.proposals.get(progress.next_proposal.into()).unwrap().into();
+                self.index_proposal_search(&proposal);
+                progress.next_proposal += 1;
+            } else if progress.next_rfp < rfps_count {
+                let rfp: RFP = self.rfps.get(progress.next_rfp.into()).unwrap().into();
+                self.index_rfp_search(&rfp);
+                progress.next_rfp += 1;
+            } else {
+                break;
+            }
+            remaining -= 1;
+        }
+        progress.done =
+            progress.next_proposal == proposals_count && progress.next_rfp == rfps_count;
+        self.search_backfill = progress.clone();
+        progress
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::collections::HashMap;
+
+    use near_sdk::serde_json::{self, json};
+
+    use super::{document_terms, RFPSearchFilters, SearchDocument, MAX_TERM_POSTINGS};
+    use crate::proposal::query::ProposalFilters;
+    use crate::rfp::{RFPSnapshot, RFP};
+    use crate::test_fixtures::{proposal_body, set_predecessor, store_proposal};
+    use crate::Contract;
+
+    fn setup() -> Contract {
+        set_predecessor("devhub.near");
+        Contract::new()
+    }
+
+    fn push_rfp(contract: &mut Contract, name: &str, labels: &[&str]) {
+        let snapshot: RFPSnapshot = serde_json::from_value(json!({
+            "editor_id": "devhub.near",
+            "timestamp": "0",
+            "block_height": "0",
+            "labels": labels,
+            "rfp_body_version": "V0",
+            "name": name,
+            "summary": "Summary",
+            "description": "Description",
+            "submission_deadline": "2000",
+            "timeline": {"status": "ACCEPTING_SUBMISSIONS"},
+            "linked_proposals": [],
+        }))
+        .unwrap();
+        let rfp = RFP {
+            id: contract.rfps.len().try_into().unwrap(),
+            author_id: "devhub.near".parse().unwrap(),
+            social_db_post_block_height: 0,
+            snapshot,
+            revision: 0,
+            legacy_block_heights: vec![],
+        };
+        contract.rfps.push(&rfp.into());
+    }
+
+    #[test]
+    fn skips_stopwords() {
+        let terms = document_terms("The indexer and the INDEXER API, a x");
+        assert_eq!(terms, vec![("indexer".to_string(), 2), ("api".to_string(), 1)]);
+    }
+
+    #[test]
+    fn backfills_and_ranks_proposals() {
+        let mut contract = setup();
+        let proposals = [
+            ("Marketing", "Indexer", "An indexer for wallet apps"),
+            ("Marketing", "Wallet", "A wallet, the best wallet"),
+            ("Research", "Wallet research", "Wallet usage study"),
+        ];
+        for (category, name, description) in proposals {
+            let body = proposal_body(
+                json!({"category": category, "name": name, "description": description}),
+            );
+            store_proposal(&mut contract, "bob.near", body);
+        }
+        push_rfp(&mut contract, "Wallet tooling", &["wallets"]);
+        push_rfp(&mut contract, "Indexers", &[]);
+
+        let progress = contract.backfill_search_index(Some(3));
+        assert!(!progress.done);
+        assert_eq!(progress.next_proposal, 3);
+        assert!(contract.backfill_search_index(None).done);
+
+        let page = contract.search_proposals("wallet".to_string(), None, Some(2), None);
+        let ids: Vec<_> = page.proposals.iter().map(|proposal| proposal.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(page.next_cursor, Some(2));
+        let page = contract.search_proposals("wallet".to_string(), None, None, Some(2));
+        assert_eq!(page.proposals[0].id, 0);
+        assert_eq!(page.next_cursor, None);
+
+        let filters = ProposalFilters { category: Some("Research".into()), ..Default::default() };
+        let page =
+            contract.search_proposals("wallet indexer".to_string(), Some(filters), None, None);
+        assert_eq!(page.proposals.len(), 1);
+        assert_eq!(page.proposals[0].id, 2);
+
+        let filters =
+            RFPSearchFilters { labels: Some(vec!["wallets".into()]), ..Default::default() };
+        let page = contract.search_rfps("wallet tooling".to_string(), Some(filters), None, None);
+        assert_eq!(page.rfps.len(), 1);
+        assert_eq!(page.rfps[0].id, 0);
+    }
+
+    #[test]
+    fn reindexes_edited_records() {
+        let mut contract = setup();
+        let body = proposal_body(json!({"name": "Indexer", "description": "An indexer"}));
+        store_proposal(&mut contract, "bob.near", body);
+        contract.index_search_document(SearchDocument::Proposal(0), "Indexer");
+        contract.index_search_document(SearchDocument::Proposal(0), "Wallet");
+        assert!(contract.search_proposals("indexer".into(), None, None, None).proposals.is_empty());
+        assert_eq!(contract.search_proposals("wallet".into(), None, None, None).proposals.len(), 1);
+        assert!(contract.proposal_search_index.get(&"indexer".to_string()).is_none());
+    }
+
+    #[test]
+    fn caps_posting_lists() {
+        let mut contract = setup();
+        let full: HashMap<u32, u32> = (0..MAX_TERM_POSTINGS as u32).map(|id| (id, 1)).collect();
+        contract.proposal_search_index.insert(&"wallet".to_string(), &full);
+        contract
+            .index_search_document(SearchDocument::Proposal(MAX_TERM_POSTINGS as u32), "Wallet");
+        let postings = contract.proposal_search_index.get(&"wallet".to_string()).unwrap();
+        assert_eq!(postings.len(), MAX_TERM_POSTINGS);
+        assert!(!postings.contains_key(&(MAX_TERM_POSTINGS as u32)));
+    }
+}
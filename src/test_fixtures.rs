@@ -0,0 +1,74 @@
+//! Contexts and records shared by the unit tests of the contract modules.
+
+use std::collections::HashSet;
+
+use near_sdk::serde_json::{self, json, Value};
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{testing_env, NearToken, Timestamp};
+
+use crate::{Contract, Proposal, ProposalSnapshot, VersionedProposalBody};
+
+/// Calls `devhub.near` from `account` without a deposit.
+pub fn set_predecessor(account: &str) {
+    set_deposit(account, NearToken::from_yoctonear(0));
+}
+
+/// Calls `devhub.near` from `account` with `deposit` attached.
+pub fn set_deposit(account: &str, deposit: NearToken) {
+    set_context(account, 0, deposit);
+}
+
+/// Calls `devhub.near` from `account` at `timestamp` with `deposit` attached.
+pub fn set_context(account: &str, timestamp: Timestamp, deposit: NearToken) {
+    testing_env!(VMContextBuilder::new()
+        .current_account_id("devhub.near".parse().unwrap())
+        .predecessor_account_id(account.parse().unwrap())
+        .block_timestamp(timestamp)
+        .attached_deposit(deposit)
+        .build());
+}
+
+/// Body of a draft Marketing proposal of `bob.near` with the fields of `overrides` replaced.
+pub fn proposal_body(overrides: Value) -> VersionedProposalBody {
+    let mut body = json!({
+        "proposal_body_version": "V0",
+        "name": "proposal",
+        "category": "Marketing",
+        "summary": "sum",
+        "description": "description",
+        "linked_proposals": [],
+        "requested_sponsorship_usd_amount": "1000",
+        "requested_sponsorship_paid_in_currency": "USDC",
+        "receiver_account": "bob.near",
+        "requested_sponsor": "neardevdao.near",
+        "supervisor": null,
+        "timeline": {"status": "DRAFT"},
+    });
+    for (field, value) in overrides.as_object().expect("The overrides must be an object") {
+        body[field] = value.clone();
+    }
+    serde_json::from_value(body).unwrap()
+}
+
+/// Stores the proposal at revision 0 without going through `add_proposal`, so none of the
+/// indexes of the contract are updated.
+pub fn store_proposal(
+    contract: &mut Contract,
+    author: &str,
+    body: VersionedProposalBody,
+) -> Proposal {
+    let proposal = Proposal {
+        id: contract.proposals.len().try_into().unwrap(),
+        author_id: author.parse().unwrap(),
+        social_db_post_block_height: 0,
+        snapshot: ProposalSnapshot {
+            editor_id: author.parse().unwrap(),
+            timestamp: 0,
+            labels: HashSet::new(),
+            body,
+        },
+        revision: 0,
+    };
+    contract.proposals.push(&proposal.clone().into());
+    proposal
+}
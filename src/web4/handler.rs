@@ -2,7 +2,8 @@ use near_sdk::{base64::prelude::*, env};
 use serde_json::json;
 
 use crate::{
-    rfp::RFP,
+    proposal::query::MAX_PAGE_LIMIT,
+    rfp::{questions::RFPQuestion, RFPId, RFP},
     web4::types::{Web4Request, Web4Response},
     Contract, Proposal,
 };
@@ -111,6 +112,7 @@ pub fn web4_get(contract: &Contract, request: Web4Request) -> Web4Response {
     );
     let redirect_path;
     let initial_props_json;
+    let mut rfp_answers = String::new();
 
     match (page, path_parts.get(2)) {
         ("community", Some(handle)) => {
@@ -147,6 +149,7 @@ pub fn web4_get(contract: &Contract, request: Web4Request) -> Web4Response {
                     let rfp_body = RFP::from(versioned_rfp).snapshot.body.latest_version();
                     title = format!(" - RFP #{} - {}", id, rfp_body.name);
                     description = rfp_body.summary;
+                    rfp_answers = render_rfp_answers(&get_all_rfp_questions(contract, id));
                 } else {
                     title = format!(" - RFP #{}", id);
                 }
@@ -212,13 +215,14 @@ pub fn web4_get(contract: &Contract, request: Web4Request) -> Web4Response {
         </a>
     </div>
 </nav>
-    <near-social-viewer src="{current_account_id}/widget/app" initialProps='{initial_props_json}' rpc="https://rpc.mainnet.fastnear.com"></near-social-viewer>
+{rfp_answers}    <near-social-viewer src="{current_account_id}/widget/app" initialProps='{initial_props_json}' rpc="https://rpc.mainnet.fastnear.com"></near-social-viewer>
     <script src="/resources/{web4_resource_account}/web4browserclient.js?blockHeight={web4_browserclient_block_height}"></script>
 </body>
 </html>"#,
         url = redirect_path,
         current_account_id = current_account_id,
         web4_resource_account = WEB4_RESOURCE_ACCOUNT,
+        rfp_answers = rfp_answers,
         web4_browserclient_block_height = web4_browserclient_block_height
     );
 
@@ -228,13 +232,78 @@ pub fn web4_get(contract: &Contract, request: Web4Request) -> Web4Response {
     }
 }
 
+/// Lists the answered questions of an RFP so that the clarifications are readable without
+/// loading the app.
+/// Collects the questions of the RFP from all the pages of `get_rfp_questions`.
+fn get_all_rfp_questions(contract: &Contract, rfp_id: RFPId) -> Vec<RFPQuestion> {
+    let mut questions = vec![];
+    let mut cursor = None;
+    loop {
+        let page = contract.get_rfp_questions(rfp_id, cursor, Some(MAX_PAGE_LIMIT));
+        questions.extend(page.questions);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            return questions;
+        }
+    }
+}
+
+fn render_rfp_answers(questions: &[RFPQuestion]) -> String {
+    let answered: Vec<String> = questions
+        .iter()
+        .filter_map(|question| {
+            let answer = question.answer.as_ref()?;
+            Some(format!(
+                "        <dt>{}</dt>\n        <dd>{} <small>&mdash; {}, <time>{}</time></small></dd>\n",
+                html_escape::encode_text(&question.text),
+                html_escape::encode_text(&answer.text),
+                html_escape::encode_text(answer.author_id.as_str()),
+                format_utc_timestamp(answer.timestamp),
+            ))
+        })
+        .collect();
+    if answered.is_empty() {
+        return String::new();
+    }
+    format!(
+        "    <section id=\"rfp-questions\">\n    <h2>Questions and answers</h2>\n    <dl>\n{}    </dl>\n    </section>\n",
+        answered.concat()
+    )
+}
+
+/// Formats a timestamp in nanoseconds as `YYYY-MM-DD HH:MM UTC`.
+fn format_utc_timestamp(timestamp: u64) -> String {
+    let seconds = timestamp / 1_000_000_000;
+    let (days, seconds_of_day) = ((seconds / 86_400) as i64, seconds % 86_400);
+    // Converts days since 1970-01-01 to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    )
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
     use std::collections::HashSet;
 
     use super::{web4_get, WEB4_RESOURCE_ACCOUNT};
     use crate::{
-        rfp::{RFPBodyV0, RFPSnapshot, VersionedRFPBody, RFP},
+        proposal::query::MAX_PAGE_LIMIT,
+        rfp::{questions::RFPQuestion, RFPBodyV0, RFPSnapshot, VersionedRFPBody, RFP},
         web4::types::Web4Response,
         CommunityInputs, Contract, Proposal, ProposalBodyV0, ProposalSnapshot,
         VersionedProposalBody,
@@ -582,7 +651,7 @@ mod tests {
                 labels: HashSet::new(),
                 body: VersionedProposalBody::V0(proposal_body),
             },
-            snapshot_history: vec![],
+            revision: 0,
         };
 
         contract.proposals.push(&proposal.clone().into());
@@ -658,7 +727,7 @@ mod tests {
                 labels: HashSet::new(),
                 body: VersionedProposalBody::V0(proposal_body),
             },
-            snapshot_history: vec![],
+            revision: 0,
         };
 
         contract.proposals.push(&proposal.clone().into());
@@ -795,10 +864,26 @@ mod tests {
                 linked_proposals: [38, 33, 26, 32, 35, 27].into(),
                 body: VersionedRFPBody::V0(rfp_body),
             },
-            snapshot_history: vec![],
+            revision: 0,
+            legacy_block_heights: vec![],
         };
 
         contract.rfps.push(&rfp.clone().into());
+        // The answered question is past the first page of questions.
+        for id in 0..MAX_PAGE_LIMIT {
+            let question: RFPQuestion = near_sdk::serde_json::from_value(json!({
+                "id": id, "author_id": "alice.near", "timestamp": "0", "text": "Unanswered", "answer": null
+            }))
+            .unwrap();
+            contract.rfp_questions.insert(&(0, id), &question);
+        }
+        let question: RFPQuestion = near_sdk::serde_json::from_value(json!({
+            "id": MAX_PAGE_LIMIT, "author_id": "alice.near", "timestamp": "0", "text": "Is <b>testnet</b> enough?",
+            "answer": {"author_id": "bob.near", "timestamp": "1728950400000000000", "text": "No"}
+        }))
+        .unwrap();
+        contract.rfp_questions.insert(&(0, MAX_PAGE_LIMIT), &question);
+        contract.rfp_question_counts.insert(&0, &(MAX_PAGE_LIMIT + 1));
 
         let response = web4_get(
             &contract,
@@ -823,6 +908,11 @@ mod tests {
                     .contains("https://near.org/not-only-devhub.near/widget/app?page=rfp&id=0"));
                 let expected_initial_props_string = json!({"page": "rfp", "id": "0"}).to_string();
                 assert!(body_string.contains(&expected_initial_props_string));
+                assert!(body_string.contains("<dt>Is &lt;b&gt;testnet&lt;/b&gt; enough?</dt>"));
+                assert!(body_string.contains(
+                    "<dd>No <small>&mdash; bob.near, <time>2024-10-15 00:00 UTC</time></small></dd>"
+                ));
+                assert!(!body_string.contains("Unanswered"));
             }
             _ => {
                 panic!("Should return Web4Response::Body");
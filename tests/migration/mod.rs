@@ -109,7 +109,19 @@ async fn test_deploy_contract_self_upgrade() -> anyhow::Result<()> {
         .await?
         .json()?;
 
-    insta::assert_json_snapshot!(get_proposal, {".snapshot.timestamp" => "[timestamp]", ".social_db_post_block_height" => "91", ".snapshot_history[0].timestamp" => "[timestamp]"});
+    insta::assert_json_snapshot!(get_proposal, {".snapshot.timestamp" => "[timestamp]", ".social_db_post_block_height" => "91"});
+
+    let get_proposal_revision: serde_json::Value = contract
+        .call("get_proposal_revision")
+        .args_json(json!({
+            "proposal_id" : 0,
+            "revision": 0
+        }))
+        .view()
+        .await?
+        .json()?;
+
+    assert_eq!(get_proposal_revision["timeline"]["status"], "DRAFT");
 
     let _set_global_labels = contract
         .call("set_global_labels")
@@ -406,7 +406,7 @@ async fn test_rfp() -> anyhow::Result<()> {
         .call("edit_rfp_timeline")
         .args_json(json!({
             "id": 0,
-            "timeline": {"status": "PROPOSAL_SELECTED" }
+            "timeline": {"status": "PROPOSAL_SELECTED", "selected_proposals": [0] }
         }))
         .max_gas()
         .deposit(deposit_amount)
@@ -430,7 +430,7 @@ async fn test_rfp() -> anyhow::Result<()> {
         .call("edit_rfp_timeline")
         .args_json(json!({
             "id": 0,
-            "timeline": {"status": "PROPOSAL_SELECTED" }
+            "timeline": {"status": "PROPOSAL_SELECTED", "selected_proposals": [0] }
         }))
         .max_gas()
         .deposit(deposit_amount)